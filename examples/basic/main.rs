@@ -4,13 +4,24 @@ extern crate log;
 use std::sync::Arc;
 
 use amethyst::{
-    assets::{AssetStorage, Loader, PrefabLoader, PrefabLoaderSystem, Processor, RonFormat},
+    assets::{
+        AssetStorage,
+        Loader,
+        PrefabData,
+        PrefabLoader,
+        PrefabLoaderSystem,
+        Processor,
+        ProgressCounter,
+        RonFormat,
+    },
     core::{
         bundle::SystemBundle,
         math::Vector3,
         transform::{Transform, TransformBundle},
         Float,
+        Time,
     },
+    derive::PrefabData,
     ecs::{
         Dispatcher,
         DispatcherBuilder,
@@ -20,6 +31,7 @@ use amethyst::{
         Resources,
         System,
         SystemData,
+        Write,
         WriteStorage,
     },
     input::{InputBundle, InputHandler, StringBindings},
@@ -45,17 +57,39 @@ use amethyst::{
     ui::UiBundle,
     utils::{application_root_dir, scene::BasicScenePrefab},
     window::{ScreenDimensions, Window, WindowBundle},
+    Error,
+};
+use amethyst_physics::{
+    CharacterController,
+    DropThrough,
+    GroundSensor,
+    OneWayPlatform,
+    PathEasing,
+    PhysicsBodyPrefab,
+    PhysicsBundle,
+    PhysicsColliderPrefab,
+    PhysicsDebugSettings,
+    PlatformPath,
+    TransformInterpolationSystem,
 };
-use amethyst_physics::PhysicsBundle;
+use serde::{Deserialize, Serialize};
 use specs_physics::{
     bodies::BodyStatus,
     colliders::Shape,
-    PhysicsBody,
     PhysicsBodyBuilder,
     PhysicsColliderBuilder,
 };
 
-pub type GamePrefabData = BasicScenePrefab<(Vec<Position>, Vec<Normal>, Vec<TexCoord>)>;
+/// The `GamePrefabData` combines the `BasicScenePrefab` with the physics
+/// `PrefabData` types, which allows us to define `PhysicsBody`s and
+/// `PhysicsCollider`s in the scene RON file.
+#[derive(Default, Deserialize, Serialize, PrefabData)]
+#[serde(default)]
+pub struct GamePrefabData {
+    scene: Option<BasicScenePrefab<(Vec<Position>, Vec<Normal>, Vec<TexCoord>)>>,
+    body: Option<PhysicsBodyPrefab>,
+    collider: Option<PhysicsColliderPrefab>,
+}
 
 /// The Player `Resources` contains player relevant data and holds a reference
 /// to the `Entity` that defines the player.
@@ -100,7 +134,7 @@ impl<'a, 'b> SimpleState for GameState<'a, 'b> {
                 sprite_sheet: character_handle.clone(),
                 sprite_number: 0,
             })
-            .with(PhysicsBodyBuilder::<Float>::from(BodyStatus::Dynamic).build())
+            .with(PhysicsBodyBuilder::<Float>::from(BodyStatus::Kinematic).build())
             .with(
                 PhysicsColliderBuilder::<Float>::from(Shape::Rectangle(
                     15.0.into(),
@@ -109,6 +143,8 @@ impl<'a, 'b> SimpleState for GameState<'a, 'b> {
                 ))
                 .build(),
             )
+            .with(CharacterController::default().with_step_height(4.0.into()))
+            .with(GroundSensor::default())
             .with(Transform::from(Vector3::new(25.0, 50.0, 0.0)))
             .build();
 
@@ -133,6 +169,47 @@ impl<'a, 'b> SimpleState for GameState<'a, 'b> {
             )
             .with(Transform::from(Vector3::new(75.0, 50.0, 0.0)))
             .build();
+
+        // create one-way platform Entity, which can be jumped onto from below
+        world
+            .create_entity()
+            .with(PhysicsBodyBuilder::<Float>::from(BodyStatus::Static).build())
+            .with(
+                PhysicsColliderBuilder::<Float>::from(Shape::Rectangle(
+                    30.0.into(),
+                    2.0.into(),
+                    1.0.into(),
+                ))
+                .build(),
+            )
+            .with(OneWayPlatform::default())
+            .with(Transform::from(Vector3::new(40.0, 65.0, 0.0)))
+            .build();
+
+        // create moving platform Entity, which carries the player up and down
+        world
+            .create_entity()
+            .with(PhysicsBodyBuilder::<Float>::from(BodyStatus::Kinematic).build())
+            .with(
+                PhysicsColliderBuilder::<Float>::from(Shape::Rectangle(
+                    15.0.into(),
+                    2.0.into(),
+                    1.0.into(),
+                ))
+                .build(),
+            )
+            .with(
+                PlatformPath::new(
+                    vec![Vector3::new(90.0, 40.0, 0.0), Vector3::new(90.0, 75.0, 0.0)]
+                        .into_iter()
+                        .map(|waypoint| waypoint.map(Float::from))
+                        .collect(),
+                    10.0.into(),
+                )
+                .with_easing(PathEasing::SmoothStep),
+            )
+            .with(Transform::from(Vector3::new(90.0, 40.0, 0.0)))
+            .build();
     }
 
     fn fixed_update(&mut self, data: StateData<GameData>) -> SimpleTrans {
@@ -174,9 +251,18 @@ impl<'a, 'b> GameState<'a, 'b> {
     /// Creates the `State` specific `Dispatcher`.
     fn create_dispatcher(&mut self, world: &mut World) {
         if self.dispatcher.is_none() {
+            // physics configuration
+            let physics_config_path = application_root_dir()
+                .expect("Failed to determine application root")
+                .join("examples/resources/physics.ron");
+
+            // the PlayerJumpSystem is added first, so it runs before the
+            // CharacterControllerSystem it conflicts with
             let mut dispatcher_builder = DispatcherBuilder::new();
-            PhysicsBundle::default()
+            dispatcher_builder.add(PlayerJumpSystem::default(), "player_jump_system", &[]);
+            PhysicsBundle::from_config_path(physics_config_path)
                 .with_debug_lines()
+                .with_interpolation()
                 .build(&mut dispatcher_builder)
                 .expect("Failed to register PhysicsBundle");
 
@@ -187,29 +273,114 @@ impl<'a, 'b> GameState<'a, 'b> {
     }
 }
 
+/// The `PlayerMovementSystem` moves the player `CharacterController` like a
+/// platformer character: walking on the X axis, requesting jumps from the
+/// `GroundSensor` and dropping through `OneWayPlatform`s. Jumping and falling
+/// are handled by the `PlayerJumpSystem` in the fixed update.
 #[derive(Default)]
 struct PlayerMovementSystem;
 
+impl PlayerMovementSystem {
+    const WALK_VELOCITY: f32 = 40.0;
+}
+
 impl<'s> System<'s> for PlayerMovementSystem {
     type SystemData = (
         Read<'s, InputHandler<StringBindings>>,
         ReadExpect<'s, Player>,
-        WriteStorage<'s, PhysicsBody<Float>>,
+        WriteStorage<'s, CharacterController>,
+        WriteStorage<'s, GroundSensor>,
+        WriteStorage<'s, DropThrough>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (input, player, mut physics_bodies) = data;
-        if let Some(physics_body) = physics_bodies.get_mut(player.player) {
+        let (input, player, mut character_controllers, mut ground_sensors, mut drop_throughs) =
+            data;
+        if let (Some(controller), Some(ground_sensor)) = (
+            character_controllers.get_mut(player.player),
+            ground_sensors.get_mut(player.player),
+        ) {
             // handle movement on X axis
-            if let Some(movement) = input.axis_value("leftright") {
-                physics_body.velocity.x = movement.into();
+            let movement = input.axis_value("leftright").unwrap_or(0.0);
+            controller.desired_velocity.x = (movement * Self::WALK_VELOCITY).into();
+
+            // handle jump and drop requests on Y axis
+            let vertical = input.axis_value("updown").unwrap_or(0.0);
+            if vertical > 0.0 {
+                ground_sensor.request_jump();
+            } else if vertical < 0.0 && controller.grounded() {
+                if let Err(err) = drop_throughs.insert(player.player, DropThrough) {
+                    warn!("Failed to insert DropThrough: {}", err);
+                }
+            }
+        }
+    }
+}
+
+/// The `PlayerJumpSystem` resolves the jump requests of the player
+/// `GroundSensor` into the vertical velocity of its `CharacterController` and
+/// applies gravity while falling. The ground state only changes with the
+/// physics step, so this has to run in the fixed update before the
+/// `CharacterControllerSystem`.
+#[derive(Default)]
+struct PlayerJumpSystem;
+
+impl PlayerJumpSystem {
+    const GRAVITY: f32 = -150.0;
+    const JUMP_VELOCITY: f32 = 80.0;
+}
+
+impl<'s> System<'s> for PlayerJumpSystem {
+    type SystemData = (
+        Read<'s, Time>,
+        ReadExpect<'s, Player>,
+        WriteStorage<'s, CharacterController>,
+        WriteStorage<'s, GroundSensor>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (time, player, mut character_controllers, mut ground_sensors) = data;
+        if let (Some(controller), Some(ground_sensor)) = (
+            character_controllers.get_mut(player.player),
+            ground_sensors.get_mut(player.player),
+        ) {
+            if ground_sensor.consume_jump() {
+                controller.desired_velocity.y = Self::JUMP_VELOCITY.into();
+            } else if controller.grounded() {
+                controller.desired_velocity.y = 0.0.into();
+            } else {
+                controller.desired_velocity.y += (Self::GRAVITY * time.fixed_seconds()).into();
             }
+        }
+    }
+}
 
-            // handle movement on Y axis
-            if let Some(movement) = input.axis_value("updown") {
-                physics_body.velocity.y = movement.into();
+/// The `PhysicsDebugToggleSystem` toggles the physics debug rendering whenever
+/// the "toggle_physics_debug" action is pressed.
+#[derive(Default)]
+struct PhysicsDebugToggleSystem {
+    pressed: bool,
+}
+
+impl<'s> System<'s> for PhysicsDebugToggleSystem {
+    type SystemData = (
+        Read<'s, InputHandler<StringBindings>>,
+        Option<Write<'s, PhysicsDebugSettings>>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (input, debug_settings) = data;
+
+        // the PhysicsDebugSettings are inserted by the State specific dispatcher
+        let pressed = input
+            .action_is_down("toggle_physics_debug")
+            .unwrap_or(false);
+        if pressed && !self.pressed {
+            if let Some(mut debug_settings) = debug_settings {
+                debug_settings.toggle();
             }
         }
+        self.pressed = pressed;
     }
 }
 
@@ -245,11 +416,9 @@ fn main() -> amethyst::Result<()> {
 
     let game_data = GameDataBuilder::default()
         .with_bundle(WindowBundle::from_config_path(display_config_path))?
-        .with_bundle(TransformBundle::new())?
         .with_bundle(
             InputBundle::<StringBindings>::new().with_bindings_from_file(key_bindings_path)?,
         )?
-        .with_bundle(UiBundle::<DefaultBackend, StringBindings>::new())?
         //.with_bundle(PhysicsBundle::default().with_debug_lines())?
         .with(
             Processor::<SpriteSheet>::new(),
@@ -262,6 +431,20 @@ fn main() -> amethyst::Result<()> {
             "player_movement_system",
             &[],
         )
+        // interpolated Transforms are written after all Transform modifications
+        // and before the global matrices are computed
+        .with(
+            TransformInterpolationSystem::default(),
+            "transform_interpolation_system",
+            &["player_movement_system"],
+        )
+        .with_bundle(TransformBundle::new().with_dep(&["transform_interpolation_system"]))?
+        .with_bundle(UiBundle::<DefaultBackend, StringBindings>::new())?
+        .with(
+            PhysicsDebugToggleSystem::default(),
+            "physics_debug_toggle_system",
+            &[],
+        )
         .with_thread_local(RenderingSystem::<DefaultBackend, _>::new(
             ExampleGraph::default(),
         ));
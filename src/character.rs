@@ -0,0 +1,112 @@
+use amethyst::{
+    core::{
+        math::{Unit, Vector3},
+        Float,
+    },
+    ecs::{Component, DenseVecStorage, Entity},
+};
+
+/// The `CharacterController` moves a kinematic `PhysicsBody` by sweeping its
+/// `PhysicsCollider` shape through the physics world and sliding along
+/// everything it hits, instead of relying on the velocity solver. Gameplay
+/// systems set the `desired_velocity` every frame, the `PhysicsBundle` resolves
+/// it into the velocity of the `PhysicsBody` and updates the ground and wall
+/// state.
+///
+/// Gravity is not applied automatically; add it to the `desired_velocity`
+/// while the character is not `grounded`.
+#[derive(Clone, Debug)]
+pub struct CharacterController {
+    /// The velocity the character tries to move with, in units per second.
+    pub desired_velocity: Vector3<Float>,
+    /// The up direction of the character, used to tell floors from walls.
+    pub up: Unit<Vector3<Float>>,
+    /// The steepest slope (in radians) the character can stand on and walk up.
+    pub max_slope: Float,
+    /// The highest obstacle the character steps onto instead of being blocked
+    /// by it.
+    pub step_height: Float,
+    /// The distance the character keeps to other colliders, which prevents it
+    /// from getting stuck in surfaces it slides along.
+    pub skin_width: Float,
+    /// The maximum number of slides per movement.
+    pub max_slides: usize,
+    pub(crate) grounded: bool,
+    pub(crate) ground_normal: Option<Unit<Vector3<Float>>>,
+    pub(crate) ground: Option<Entity>,
+    pub(crate) touching_wall: bool,
+}
+
+impl Component for CharacterController {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Default for CharacterController {
+    fn default() -> Self {
+        Self {
+            desired_velocity: Vector3::zeros(),
+            up: Vector3::y_axis(),
+            max_slope: Float::from(std::f32::consts::FRAC_PI_4),
+            step_height: Float::from(0.0),
+            skin_width: Float::from(0.01),
+            max_slides: 4,
+            grounded: false,
+            ground_normal: None,
+            ground: None,
+            touching_wall: false,
+        }
+    }
+}
+
+impl CharacterController {
+    /// Sets the up direction of the character.
+    pub fn with_up(mut self, up: Unit<Vector3<Float>>) -> Self {
+        self.up = up;
+        self
+    }
+
+    /// Sets the steepest slope (in radians) the character can walk up.
+    pub fn with_max_slope(mut self, max_slope: Float) -> Self {
+        self.max_slope = max_slope;
+        self
+    }
+
+    /// Sets the highest obstacle the character steps onto.
+    pub fn with_step_height(mut self, step_height: Float) -> Self {
+        self.step_height = step_height;
+        self
+    }
+
+    /// Sets the distance the character keeps to other colliders.
+    pub fn with_skin_width(mut self, skin_width: Float) -> Self {
+        self.skin_width = skin_width;
+        self
+    }
+
+    /// Checks whether the character is standing on a walkable surface.
+    pub fn grounded(&self) -> bool {
+        self.grounded
+    }
+
+    /// Returns the normal of the walkable surface the character is standing
+    /// on.
+    pub fn ground_normal(&self) -> Option<Unit<Vector3<Float>>> {
+        self.ground_normal
+    }
+
+    /// Returns the `Entity` the character is standing on.
+    pub fn ground(&self) -> Option<Entity> {
+        self.ground
+    }
+
+    /// Checks whether the character was blocked by a surface too steep to walk
+    /// up during its last movement.
+    pub fn touching_wall(&self) -> bool {
+        self.touching_wall
+    }
+
+    /// Checks whether a surface with the given normal can be stood on.
+    pub(crate) fn is_walkable(&self, normal: &Vector3<Float>) -> bool {
+        normal.dot(&self.up) >= self.max_slope.cos()
+    }
+}
@@ -0,0 +1,100 @@
+use amethyst::core::{math::Vector3, Float};
+use serde::{Deserialize, Serialize};
+
+use specs_physics::Physics;
+
+/// The `PhysicsConfig` defines the parameters of the physics world. It is
+/// applied when the `Physics` resource is created and can be loaded from a RON
+/// file using `amethyst::config::Config`.
+///
+/// ```ron
+/// (
+///     gravity: (0.0, -9.81, 0.0),
+///     timestep: 0.016666668,
+///     max_velocity_iterations: 8,
+///     max_position_iterations: 3,
+///     max_ccd_substeps: 0,
+/// )
+/// ```
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PhysicsConfig {
+    /// The gravity vector of the physics world.
+    pub gravity: (f32, f32, f32),
+    /// The duration (in seconds) of a single physics step.
+    pub timestep: f32,
+    /// The maximum number of iterations the velocity constraint solver
+    /// performs per step.
+    pub max_velocity_iterations: usize,
+    /// The maximum number of iterations the position constraint solver
+    /// performs per step.
+    pub max_position_iterations: usize,
+    /// The maximum number of substeps continuous collision detection performs
+    /// per step. This does not enable continuous collision detection for any
+    /// collider by itself; it only limits how often colliders that have it
+    /// enabled are swept per step, and `0` disables it for the whole world.
+    pub max_ccd_substeps: usize,
+}
+
+impl Default for PhysicsConfig {
+    fn default() -> Self {
+        Self {
+            gravity: (0.0, 0.0, 0.0),
+            timestep: 1.0 / 60.0,
+            max_velocity_iterations: 8,
+            max_position_iterations: 3,
+            max_ccd_substeps: 0,
+        }
+    }
+}
+
+impl PhysicsConfig {
+    /// Creates the `Physics` resource and applies this `PhysicsConfig` to its
+    /// world.
+    pub(crate) fn create_physics(&self) -> Physics<Float> {
+        let mut physics = Physics::default();
+
+        let world = physics.world_mut();
+        let (x, y, z) = self.gravity;
+        world.set_gravity(Vector3::new(x.into(), y.into(), z.into()));
+        world.set_timestep(self.timestep.into());
+
+        let integration_parameters = world.integration_parameters_mut();
+        integration_parameters.max_velocity_iterations = self.max_velocity_iterations;
+        integration_parameters.max_position_iterations = self.max_position_iterations;
+        integration_parameters.max_ccd_substeps = self.max_ccd_substeps;
+
+        physics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use amethyst::core::{math::Vector3, Float};
+
+    use super::PhysicsConfig;
+
+    #[test]
+    fn create_physics() {
+        let config = PhysicsConfig {
+            gravity: (0.0, -9.81, 0.0),
+            timestep: 0.01,
+            max_velocity_iterations: 4,
+            max_position_iterations: 2,
+            max_ccd_substeps: 3,
+        };
+        let physics = config.create_physics();
+
+        let world = physics.world();
+        assert_eq!(
+            *world.gravity(),
+            Vector3::new(0.0f32, -9.81, 0.0).map(Float::from)
+        );
+        assert_eq!(world.timestep(), Float::from(0.01f32));
+
+        let integration_parameters = world.integration_parameters();
+        assert_eq!(integration_parameters.max_velocity_iterations, 4);
+        assert_eq!(integration_parameters.max_position_iterations, 2);
+        assert_eq!(integration_parameters.max_ccd_substeps, 3);
+    }
+}
@@ -0,0 +1,330 @@
+use std::{f32::consts::PI, fmt::Write as FmtWrite, fs, io, path::Path};
+
+use amethyst::{
+    core::math::{Point3, Vector3},
+    renderer::palette::Srgba,
+};
+
+/// Number of line segments used to approximate a full circle.
+pub(crate) const CIRCLE_SEGMENTS: usize = 32;
+
+/// Padding (in world units) around the content of exported SVG files.
+const SVG_PADDING: f32 = 1.0;
+
+/// The `PhysicsDebugGeometry` resource contains the backend-agnostic debug
+/// geometry of the physics world generated by the `DebugSystem`. It is
+/// regenerated every frame and can either be rendered via `DebugLines` or
+/// exported, e.g. to an SVG file, which allows snapshotting physics scenes
+/// without a GPU.
+#[derive(Clone, Debug, Default)]
+pub struct PhysicsDebugGeometry {
+    /// The lines of the debug geometry.
+    pub lines: Vec<DebugLine>,
+    /// The circles of the debug geometry.
+    pub circles: Vec<DebugCircle>,
+    /// The text labels of the debug geometry.
+    pub labels: Vec<DebugLabel>,
+}
+
+/// A world-space line of the `PhysicsDebugGeometry`.
+#[derive(Clone, Debug)]
+pub struct DebugLine {
+    pub start: Point3<f32>,
+    pub end: Point3<f32>,
+    pub color: Srgba,
+}
+
+/// A world-space circle of the `PhysicsDebugGeometry`, lying in the plane
+/// perpendicular to its `normal`.
+#[derive(Clone, Debug)]
+pub struct DebugCircle {
+    pub center: Point3<f32>,
+    pub normal: Vector3<f32>,
+    pub radius: f32,
+    pub color: Srgba,
+}
+
+/// A world-space text label of the `PhysicsDebugGeometry`.
+#[derive(Clone, Debug)]
+pub struct DebugLabel {
+    pub position: Point3<f32>,
+    pub text: String,
+    pub color: Srgba,
+}
+
+impl PhysicsDebugGeometry {
+    /// Removes all geometry.
+    pub fn clear(&mut self) {
+        self.lines.clear();
+        self.circles.clear();
+        self.labels.clear();
+    }
+
+    /// Checks whether there is any geometry.
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty() && self.circles.is_empty() && self.labels.is_empty()
+    }
+
+    /// Adds a line between the given world-space points.
+    pub fn add_line(&mut self, start: Point3<f32>, end: Point3<f32>, color: Srgba) {
+        self.lines.push(DebugLine { start, end, color });
+    }
+
+    /// Adds a circle around the given world-space center.
+    pub fn add_circle(
+        &mut self,
+        center: Point3<f32>,
+        normal: Vector3<f32>,
+        radius: f32,
+        color: Srgba,
+    ) {
+        self.circles.push(DebugCircle {
+            center,
+            normal,
+            radius,
+            color,
+        });
+    }
+
+    /// Adds a text label at the given world-space position.
+    pub fn add_label(&mut self, position: Point3<f32>, text: impl Into<String>, color: Srgba) {
+        self.labels.push(DebugLabel {
+            position,
+            text: text.into(),
+            color,
+        });
+    }
+
+    /// Renders the geometry, projected onto the xy plane, into an SVG
+    /// document. The y axis is flipped so the SVG shows the scene the way a 2D
+    /// camera looking down the negative z axis does.
+    pub fn to_svg(&self) -> String {
+        let (mins, maxs) = self.bounds();
+        let (width, height) = (maxs.x - mins.x, maxs.y - mins.y);
+
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}">"#,
+            mins.x, -maxs.y, width, height
+        );
+        let _ = writeln!(
+            svg,
+            r#"<g fill="none" stroke-width="{}">"#,
+            (width.max(height) / 500.0).max(0.01)
+        );
+
+        for line in &self.lines {
+            write_svg_line(&mut svg, &line.start, &line.end, &line.color);
+        }
+
+        for circle in &self.circles {
+            // circles facing the viewer stay circles, all others are projected
+            if circle.normal.normalize().z.abs() > 0.999 {
+                let _ = writeln!(
+                    svg,
+                    r#"<circle cx="{}" cy="{}" r="{}" {}/>"#,
+                    circle.center.x,
+                    -circle.center.y,
+                    circle.radius,
+                    svg_stroke(&circle.color)
+                );
+            } else {
+                for (start, end) in circle.segments(CIRCLE_SEGMENTS) {
+                    write_svg_line(&mut svg, &start, &end, &circle.color);
+                }
+            }
+        }
+
+        let _ = writeln!(svg, "</g>");
+
+        for label in &self.labels {
+            let _ = writeln!(
+                svg,
+                r#"<text x="{}" y="{}" font-size="{}" {}>{}</text>"#,
+                label.position.x,
+                -label.position.y,
+                (width.max(height) / 50.0).max(0.1),
+                svg_fill(&label.color),
+                escape_xml(&label.text)
+            );
+        }
+
+        let _ = writeln!(svg, "</svg>");
+        svg
+    }
+
+    /// Writes the geometry as SVG document to the given path. See `to_svg`.
+    pub fn write_svg(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_svg())
+    }
+
+    /// Returns the padded bounds of all geometry on the xy plane.
+    fn bounds(&self) -> (Point3<f32>, Point3<f32>) {
+        let points = self
+            .lines
+            .iter()
+            .flat_map(|line| vec![line.start, line.end])
+            .chain(self.circles.iter().flat_map(|circle| {
+                let radius = Vector3::new(circle.radius, circle.radius, circle.radius);
+                vec![circle.center - radius, circle.center + radius]
+            }))
+            .chain(self.labels.iter().map(|label| label.position));
+
+        let mut bounds: Option<(Point3<f32>, Point3<f32>)> = None;
+        for point in points {
+            bounds = Some(match bounds {
+                Some((mins, maxs)) => (mins.inf(&point), maxs.sup(&point)),
+                None => (point, point),
+            });
+        }
+
+        let padding = Vector3::new(SVG_PADDING, SVG_PADDING, SVG_PADDING);
+        let (mins, maxs) = bounds.unwrap_or((Point3::origin(), Point3::origin()));
+        (mins - padding, maxs + padding)
+    }
+}
+
+impl DebugCircle {
+    /// Approximates the circle with the given number of line segments.
+    pub fn segments(&self, count: usize) -> Vec<(Point3<f32>, Point3<f32>)> {
+        let normal = self.normal.normalize();
+        let reference = if normal.x.abs() < 0.9 {
+            Vector3::x()
+        } else {
+            Vector3::y()
+        };
+        let u = normal.cross(&reference).normalize() * self.radius;
+        let v = normal.cross(&u);
+
+        let point_at = |i: usize| {
+            let angle = 2.0 * PI * i as f32 / count as f32;
+            self.center + u * angle.cos() + v * angle.sin()
+        };
+
+        (0..count).map(|i| (point_at(i), point_at(i + 1))).collect()
+    }
+}
+
+fn write_svg_line(svg: &mut String, start: &Point3<f32>, end: &Point3<f32>, color: &Srgba) {
+    let _ = writeln!(
+        svg,
+        r#"<line x1="{}" y1="{}" x2="{}" y2="{}" {}/>"#,
+        start.x,
+        -start.y,
+        end.x,
+        -end.y,
+        svg_stroke(color)
+    );
+}
+
+fn svg_stroke(color: &Srgba) -> String {
+    let (rgb, alpha) = svg_color(color);
+    format!(r#"stroke="{}" stroke-opacity="{}""#, rgb, alpha)
+}
+
+fn svg_fill(color: &Srgba) -> String {
+    let (rgb, alpha) = svg_color(color);
+    format!(r#"fill="{}" fill-opacity="{}""#, rgb, alpha)
+}
+
+fn svg_color(color: &Srgba) -> (String, f32) {
+    let channel = |value: f32| (value.max(0.0).min(1.0) * 255.0).round() as u8;
+    (
+        format!(
+            "rgb({},{},{})",
+            channel(color.color.red),
+            channel(color.color.green),
+            channel(color.color.blue)
+        ),
+        color.alpha,
+    )
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn white() -> Srgba {
+        Srgba::new(1.0, 1.0, 1.0, 1.0)
+    }
+
+    #[test]
+    fn empty_geometry() {
+        let geometry = PhysicsDebugGeometry::default();
+        assert!(geometry.is_empty());
+
+        let svg = geometry.to_svg();
+        assert!(svg.contains(r#"viewBox="-1 -1 2 2""#));
+        assert!(!svg.contains("<line"));
+        assert!(!svg.contains("<circle"));
+        assert!(!svg.contains("<text"));
+    }
+
+    #[test]
+    fn view_box_flips_y_axis() {
+        let mut geometry = PhysicsDebugGeometry::default();
+        geometry.add_line(
+            Point3::new(1.0, 1.0, 0.0),
+            Point3::new(4.0, 2.0, 0.0),
+            white(),
+        );
+
+        let (mins, maxs) = geometry.bounds();
+        assert_eq!(mins, Point3::new(0.0, 0.0, -1.0));
+        assert_eq!(maxs, Point3::new(5.0, 3.0, 1.0));
+
+        let svg = geometry.to_svg();
+        assert!(svg.contains(r#"viewBox="0 -3 5 3""#));
+        assert!(svg.contains(r#"x1="1" y1="-1" x2="4" y2="-2""#));
+    }
+
+    #[test]
+    fn circle_facing_viewer() {
+        let mut geometry = PhysicsDebugGeometry::default();
+        geometry.add_circle(Point3::new(1.0, 2.0, 0.0), Vector3::z(), 0.5, white());
+
+        let svg = geometry.to_svg();
+        assert!(svg.contains(r#"<circle cx="1" cy="-2" r="0.5""#));
+        assert!(!svg.contains("<line"));
+    }
+
+    #[test]
+    fn projected_circle() {
+        let mut geometry = PhysicsDebugGeometry::default();
+        geometry.add_circle(Point3::new(1.0, 2.0, 0.0), Vector3::x(), 0.5, white());
+
+        let svg = geometry.to_svg();
+        assert!(!svg.contains("<circle"));
+        assert_eq!(svg.matches("<line").count(), CIRCLE_SEGMENTS);
+    }
+
+    #[test]
+    fn labels_are_escaped() {
+        let mut geometry = PhysicsDebugGeometry::default();
+        geometry.add_label(Point3::origin(), "<a> & <b>", white());
+
+        let svg = geometry.to_svg();
+        assert!(svg.contains("&lt;a&gt; &amp; &lt;b&gt;</text>"));
+        assert!(!svg.contains("<a>"));
+    }
+
+    #[test]
+    fn write_svg_to_file() {
+        let mut geometry = PhysicsDebugGeometry::default();
+        geometry.add_line(Point3::origin(), Point3::new(1.0, 1.0, 0.0), white());
+
+        let path = std::env::temp_dir().join("amethyst_physics_write_svg_to_file.svg");
+        geometry.write_svg(&path).unwrap();
+        let written = fs::read_to_string(&path).unwrap();
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(written, geometry.to_svg());
+    }
+}
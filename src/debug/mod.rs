@@ -0,0 +1,129 @@
+use amethyst::renderer::palette::Srgba;
+
+pub(crate) use self::geometry::CIRCLE_SEGMENTS;
+pub use self::geometry::{DebugCircle, DebugLabel, DebugLine, PhysicsDebugGeometry};
+
+mod geometry;
+
+/// The `PhysicsDebugSettings` resource controls the debug rendering of the
+/// physics world. It can be modified at runtime, e.g. to toggle the debug
+/// rendering with an input action.
+#[derive(Clone, Debug)]
+pub struct PhysicsDebugSettings {
+    /// Master switch for all debug rendering.
+    pub enabled: bool,
+    /// Draw the outlines of solid colliders.
+    pub colliders: bool,
+    /// Draw the outlines of sensor colliders.
+    pub sensors: bool,
+    /// Draw the axis-aligned bounding boxes of colliders.
+    pub aabbs: bool,
+    /// Draw the (loosened) bounds of the broad-phase proxies of colliders.
+    pub broad_phase: bool,
+    /// The number of overlapping broad-phase proxies from which on a proxy is
+    /// highlighted as hot spot.
+    pub broad_phase_hot_threshold: usize,
+    /// Draw contact points and contact normals scaled by penetration depth.
+    pub contacts: bool,
+    /// Draw the linear and angular velocities of bodies.
+    pub velocities: bool,
+    /// Draw the net forces acting on bodies, estimated from the change of their
+    /// velocity between two physics steps.
+    pub forces: bool,
+    /// Draw joints between bodies.
+    pub joints: bool,
+    /// Label colliders with the id of their `Entity`.
+    pub labels: bool,
+    /// The width of the debug lines; applied to the `DebugLinesParams` when
+    /// rendering via `DebugLines`.
+    pub line_width: f32,
+    /// The size of the crosses marking points, e.g. contact points.
+    pub marker_size: f32,
+    /// The factor velocity and force vectors are scaled with before drawing.
+    pub vector_scale: f32,
+    /// The colors used for the individual categories.
+    pub colors: PhysicsDebugColors,
+}
+
+impl Default for PhysicsDebugSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            colliders: true,
+            sensors: true,
+            aabbs: false,
+            broad_phase: false,
+            broad_phase_hot_threshold: 8,
+            contacts: false,
+            velocities: false,
+            forces: false,
+            joints: false,
+            labels: false,
+            line_width: 1.0,
+            marker_size: 1.0,
+            vector_scale: 1.0,
+            colors: PhysicsDebugColors::default(),
+        }
+    }
+}
+
+impl PhysicsDebugSettings {
+    /// Toggles the debug rendering on or off.
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+    }
+}
+
+/// The colors used by the debug rendering.
+#[derive(Clone, Debug)]
+pub struct PhysicsDebugColors {
+    /// Color of solid colliders attached to dynamic bodies.
+    pub dynamic_body: Srgba,
+    /// Color of solid colliders attached to dynamic bodies that are asleep.
+    pub sleeping_body: Srgba,
+    /// Color of solid colliders attached to static bodies or the ground.
+    pub static_body: Srgba,
+    /// Color of solid colliders attached to kinematic bodies.
+    pub kinematic_body: Srgba,
+    /// Color of solid colliders attached to disabled bodies.
+    pub disabled_body: Srgba,
+    /// Color of sensor collider outlines.
+    pub sensor: Srgba,
+    /// Color of axis-aligned bounding boxes.
+    pub aabb: Srgba,
+    /// Color of broad-phase proxy bounds.
+    pub broad_phase: Srgba,
+    /// Color of broad-phase proxy bounds that are hot spots.
+    pub broad_phase_hot: Srgba,
+    /// Color of contact points and normals.
+    pub contact: Srgba,
+    /// Color of velocity vectors.
+    pub velocity: Srgba,
+    /// Color of force vectors.
+    pub force: Srgba,
+    /// Color of joints.
+    pub joint: Srgba,
+    /// Color of labels.
+    pub label: Srgba,
+}
+
+impl Default for PhysicsDebugColors {
+    fn default() -> Self {
+        Self {
+            dynamic_body: Srgba::new(0.81, 0.0, 0.5, 1.0),
+            sleeping_body: Srgba::new(0.4, 0.0, 0.25, 1.0),
+            static_body: Srgba::new(0.55, 0.55, 0.75, 1.0),
+            kinematic_body: Srgba::new(0.95, 0.6, 0.1, 1.0),
+            disabled_body: Srgba::new(0.3, 0.3, 0.3, 1.0),
+            sensor: Srgba::new(0.13, 0.65, 0.94, 1.0),
+            aabb: Srgba::new(0.5, 0.5, 0.5, 1.0),
+            broad_phase: Srgba::new(0.3, 0.5, 0.3, 1.0),
+            broad_phase_hot: Srgba::new(1.0, 0.1, 0.1, 1.0),
+            contact: Srgba::new(1.0, 0.85, 0.0, 1.0),
+            velocity: Srgba::new(0.2, 0.9, 0.3, 1.0),
+            force: Srgba::new(0.9, 0.2, 0.2, 1.0),
+            joint: Srgba::new(1.0, 0.5, 0.0, 1.0),
+            label: Srgba::new(1.0, 1.0, 1.0, 1.0),
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use amethyst::{
+    core::{
+        math::{Unit, Vector3},
+        Float,
+    },
+    ecs::Entity,
+};
+
+/// The `CollisionEvent` is published on the `EventChannel<CollisionEvent>`
+/// resource whenever two `Entity`s start or stop touching each other. Systems
+/// can subscribe to these events by registering a `ReaderId` on said channel.
+#[derive(Clone, Debug)]
+pub struct CollisionEvent {
+    /// The first `Entity` involved in the collision.
+    pub entity1: Entity,
+    /// The second `Entity` involved in the collision.
+    pub entity2: Entity,
+    /// Whether the event originates from a contact or a proximity (sensor)
+    /// query.
+    pub kind: CollisionKind,
+    /// Whether the collision started or stopped.
+    pub phase: CollisionPhase,
+    /// The contact normal pointing from `entity1` towards `entity2`. Only
+    /// available for started contacts.
+    pub normal: Option<Unit<Vector3<Float>>>,
+    /// The penetration depth of the deepest contact point. Only available for
+    /// started contacts.
+    pub depth: Option<Float>,
+}
+
+impl CollisionEvent {
+    /// Returns the `Entity` that collided with the given `Entity`, if the given
+    /// `Entity` is part of this `CollisionEvent`.
+    pub fn other(&self, entity: Entity) -> Option<Entity> {
+        if self.entity1 == entity {
+            Some(self.entity2)
+        } else if self.entity2 == entity {
+            Some(self.entity1)
+        } else {
+            None
+        }
+    }
+}
+
+/// The source of a `CollisionEvent`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CollisionKind {
+    /// Two solid colliders touch each other.
+    Contact,
+    /// At least one of the colliders is a sensor and they intersect.
+    Proximity,
+}
+
+/// The phase of a `CollisionEvent`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CollisionPhase {
+    /// The colliders started touching/intersecting during the last physics
+    /// step.
+    Started,
+    /// The colliders stopped touching/intersecting during the last physics
+    /// step.
+    Stopped,
+}
+
+/// The `TriggerEvent` is published on the `EventChannel<TriggerEvent>` resource
+/// whenever an `Entity` enters or exits a sensor collider. If enabled on the
+/// `PhysicsBundle`, an additional `TriggerEventKind::Stay` event is published
+/// every frame for each `Entity` that remains inside a sensor.
+#[derive(Clone, Debug)]
+pub struct TriggerEvent {
+    /// The `Entity` of the sensor collider.
+    pub sensor: Entity,
+    /// The `Entity` that triggered the sensor.
+    pub entity: Entity,
+    /// Whether the `Entity` entered, stayed in or exited the sensor.
+    pub kind: TriggerEventKind,
+}
+
+/// The kind of a `TriggerEvent`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TriggerEventKind {
+    /// The `Entity` entered the sensor during the last physics step.
+    Enter,
+    /// The `Entity` was already inside the sensor and still is.
+    Stay,
+    /// The `Entity` exited the sensor during the last physics step.
+    Exit,
+}
@@ -0,0 +1,128 @@
+use amethyst::{
+    core::{
+        math::{Point3, Unit, Vector3},
+        Float,
+    },
+    ecs::{Component, DenseVecStorage, Entity, FlaggedStorage},
+};
+
+/// The `PhysicsJoint` connects the `PhysicsBody` of its `Entity` to the
+/// `PhysicsBody` of the `target` `Entity`, or to a fixed point of the world if
+/// there is no `target`. The joint is added to the physics world once both
+/// bodies exist and removed again together with the `PhysicsJoint`. Modifying
+/// the `PhysicsJoint` recreates the joint, keeping the current relative
+/// orientation of both bodies.
+///
+/// ```ignore
+/// let joint = PhysicsJoint::new(JointKind::Ball, anchor_entity)
+///     .with_anchor(Point3::new(0.0.into(), 5.0.into(), 0.0.into()));
+/// ```
+#[derive(Clone, Debug)]
+pub struct PhysicsJoint {
+    /// The `Entity` the joint connects to. Without a `target`, the joint
+    /// connects to the world.
+    pub target: Option<Entity>,
+    /// The kind of the joint.
+    pub kind: JointKind,
+    /// The anchor of the joint, relative to the `PhysicsBody` of the `Entity`.
+    pub anchor: Point3<Float>,
+    /// The anchor of the joint, relative to the `PhysicsBody` of the `target`
+    /// or in world-space without a `target`.
+    pub target_anchor: Point3<Float>,
+}
+
+impl Component for PhysicsJoint {
+    type Storage = FlaggedStorage<Self, DenseVecStorage<Self>>;
+}
+
+impl PhysicsJoint {
+    /// Creates a new `PhysicsJoint` that connects to the given `Entity`.
+    pub fn new(kind: JointKind, target: Entity) -> Self {
+        Self {
+            target: Some(target),
+            kind,
+            anchor: Point3::origin(),
+            target_anchor: Point3::origin(),
+        }
+    }
+
+    /// Creates a new `PhysicsJoint` that connects to the world.
+    pub fn to_world(kind: JointKind) -> Self {
+        Self {
+            target: None,
+            kind,
+            anchor: Point3::origin(),
+            target_anchor: Point3::origin(),
+        }
+    }
+
+    /// Sets the anchor of the joint, relative to the `PhysicsBody` of the
+    /// `Entity`.
+    pub fn with_anchor(mut self, anchor: Point3<Float>) -> Self {
+        self.anchor = anchor;
+        self
+    }
+
+    /// Sets the anchor of the joint, relative to the `PhysicsBody` of the
+    /// `target` or in world-space without a `target`.
+    pub fn with_target_anchor(mut self, target_anchor: Point3<Float>) -> Self {
+        self.target_anchor = target_anchor;
+        self
+    }
+}
+
+/// The `JointKind` defines which degrees of freedom a `PhysicsJoint` leaves
+/// between the connected bodies. Axes are relative to the `PhysicsBody` of the
+/// `Entity` the `PhysicsJoint` belongs to.
+#[derive(Clone, Debug)]
+pub enum JointKind {
+    /// Locks both bodies together.
+    Fixed,
+    /// Allows rotation around the given axis only, like a hinge.
+    Revolute {
+        /// The rotation axis.
+        axis: Unit<Vector3<Float>>,
+        /// The minimum and maximum angle (in radians).
+        limits: Option<JointLimits>,
+        /// The motor driving the angular velocity around the axis.
+        motor: Option<JointMotor>,
+    },
+    /// Allows translation along the given axis only, like a slider.
+    Prismatic {
+        /// The translation axis.
+        axis: Unit<Vector3<Float>>,
+        /// The minimum and maximum offset along the axis.
+        limits: Option<JointLimits>,
+        /// The motor driving the linear velocity along the axis.
+        motor: Option<JointMotor>,
+    },
+    /// Allows rotation around the anchors in all directions.
+    Ball,
+    /// Pulls the anchor towards the target anchor with a limited force, e.g.
+    /// for dragging bodies with the mouse.
+    Mouse {
+        /// The maximum force applied to the `PhysicsBody`.
+        max_force: Float,
+    },
+}
+
+/// The `JointLimits` restrict the movement along the free axis of a
+/// `PhysicsJoint`.
+#[derive(Clone, Copy, Debug)]
+pub struct JointLimits {
+    /// The minimum angle (in radians) or offset.
+    pub min: Float,
+    /// The maximum angle (in radians) or offset.
+    pub max: Float,
+}
+
+/// The `JointMotor` drives the velocity of the `PhysicsBody` along the free
+/// axis of a `PhysicsJoint`, relative to the `target`.
+#[derive(Clone, Copy, Debug)]
+pub struct JointMotor {
+    /// The angular (in radians per second) or linear velocity the motor
+    /// drives towards.
+    pub target_velocity: Float,
+    /// The maximum change of velocity per second the motor can apply.
+    pub max_acceleration: Float,
+}
@@ -1,18 +1,47 @@
 #[macro_use]
 extern crate log;
 
-pub use systems::PhysicsBundle;
+pub use self::{
+    character::CharacterController,
+    config::PhysicsConfig,
+    debug::{PhysicsDebugColors, PhysicsDebugGeometry, PhysicsDebugSettings},
+    joints::{JointKind, JointLimits, JointMotor, PhysicsJoint},
+    platformer::{
+        DropThrough,
+        GroundSensor,
+        OneWayPlatform,
+        PathEasing,
+        PathMode,
+        PlatformPath,
+        PlatformRider,
+    },
+    prefab::{PhysicsBodyPrefab, PhysicsColliderPrefab},
+    query::{screen_ray, PhysicsQuery, QueryFilter, RaycastHit, ShapeCastHit},
+    systems::{PhysicsBundle, TransformInterpolationSystem},
+};
 
 use amethyst::{
-    core::{math::Vector3, Float},
-    ecs::{Component, DenseVecStorage, FlaggedStorage},
+    core::{math::Isometry3, Float},
+    ecs::{Component, DenseVecStorage, Entity, FlaggedStorage, NullStorage},
 };
-use specs_physics::bodies::Position;
+use specs_physics::{bodies::Position, nphysics::object::Collider};
+
+pub mod character;
+pub mod config;
+pub mod debug;
+pub mod events;
+pub mod joints;
+pub mod platformer;
+pub mod prefab;
+pub mod query;
 
 mod systems;
 
+/// The `PhysicsTransform` is the `Position` type used by `specs_physics`. It
+/// carries the full `Isometry3` (translation and rotation) of an `Entity` and
+/// is kept in sync with its `Transform` by the `PhysicsBundle` systems.
 pub struct PhysicsTransform {
-    position: Vector3<Float>,
+    isometry: Isometry3<Float>,
 }
 
 impl Component for PhysicsTransform {
@@ -20,19 +49,127 @@ impl Component for PhysicsTransform {
 }
 
 impl Position<Float> for PhysicsTransform {
-    fn position(&self) -> (Float, Float, Float) {
-        (self.position.x, self.position.y, self.position.z)
+    fn isometry(&self) -> &Isometry3<Float> {
+        &self.isometry
     }
 
-    fn set_position(&mut self, x: Float, y: Float, z: Float) {
-        self.position.x = x;
-        self.position.y = y;
-        self.position.z = z;
+    fn isometry_mut(&mut self) -> &mut Isometry3<Float> {
+        &mut self.isometry
+    }
+}
+
+/// The `SyncedIsometry` remembers the `Isometry3` that was last written into an
+/// `Entity`s `Transform` by the `PhysicsBundle`. It allows us to tell physics
+/// driven `Transform` modifications apart from modifications made by user
+/// systems, which are treated as teleports.
+pub(crate) struct SyncedIsometry(pub(crate) Isometry3<Float>);
+
+impl Component for SyncedIsometry {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// The `InterpolatedIsometry` keeps the poses of the two most recent physics
+/// steps of an `Entity`. The `TransformInterpolationSystem` blends between them
+/// to render smooth movement between fixed physics steps.
+pub(crate) struct InterpolatedIsometry {
+    pub(crate) previous: Isometry3<Float>,
+    pub(crate) current: Isometry3<Float>,
+}
+
+impl Component for InterpolatedIsometry {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl From<Isometry3<Float>> for InterpolatedIsometry {
+    fn from(isometry: Isometry3<Float>) -> Self {
+        Self {
+            previous: isometry,
+            current: isometry,
+        }
+    }
+}
+
+/// Marker `Component` for `Entity`s whose `Transform` should snap to the latest
+/// physics step instead of being interpolated, even if interpolation is enabled
+/// on the `PhysicsBundle`.
+#[derive(Default)]
+pub struct NoInterpolation;
+
+impl Component for NoInterpolation {
+    type Storage = NullStorage<Self>;
+}
+
+impl From<Isometry3<Float>> for PhysicsTransform {
+    fn from(isometry: Isometry3<Float>) -> Self {
+        Self { isometry }
     }
 }
 
-impl From<Vector3<Float>> for PhysicsTransform {
-    fn from(position: Vector3<Float>) -> Self {
-        Self { position }
+/// The `Triggered` `Component` is attached to sensor `Entity`s while other
+/// `Entity`s are inside of them. It is maintained by the `PhysicsBundle` and
+/// removed again once the sensor is empty.
+#[derive(Clone, Debug, Default)]
+pub struct Triggered {
+    entities: Vec<Entity>,
+}
+
+impl Component for Triggered {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Triggered {
+    /// Returns the `Entity`s that are currently inside the sensor.
+    pub fn entities(&self) -> &[Entity] {
+        &self.entities
+    }
+
+    /// Checks whether the given `Entity` is currently inside the sensor.
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.entities.contains(&entity)
+    }
+
+    /// Adds the given `Entity` and returns `true` if it was not inside the
+    /// sensor before.
+    pub(crate) fn insert(&mut self, entity: Entity) -> bool {
+        if self.contains(entity) {
+            false
+        } else {
+            self.entities.push(entity);
+            true
+        }
     }
+
+    /// Removes the given `Entity` and returns `true` if it was inside the
+    /// sensor before.
+    pub(crate) fn remove(&mut self, entity: Entity) -> bool {
+        let len = self.entities.len();
+        self.entities.retain(|e| *e != entity);
+        self.entities.len() != len
+    }
+
+    /// Retains only the `Entity`s that satisfy the given predicate and returns
+    /// the removed ones.
+    pub(crate) fn retain<F>(&mut self, mut predicate: F) -> Vec<Entity>
+    where
+        F: FnMut(Entity) -> bool,
+    {
+        let (retained, removed) = self.entities.iter().cloned().partition(|e| predicate(*e));
+        self.entities = retained;
+        removed
+    }
+
+    /// Checks whether the sensor is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+}
+
+/// Returns the `Entity` a `Collider` of the physics world belongs to.
+/// `specs_physics` stores the `Entity` as user data of every `Collider` it
+/// creates.
+pub(crate) fn collider_entity(collider: &Collider<Float>) -> Option<Entity> {
+    collider
+        .user_data()
+        .and_then(|user_data| user_data.downcast_ref::<Entity>())
+        .cloned()
 }
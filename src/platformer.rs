@@ -0,0 +1,439 @@
+use std::collections::HashSet;
+
+use amethyst::{
+    core::{
+        math::{Unit, Vector3},
+        Float,
+    },
+    ecs::{Component, DenseVecStorage, Entity, NullStorage},
+};
+
+/// The `GroundSensor` tracks whether an `Entity` stands on walkable ground. It
+/// is updated by the `PhysicsBundle` after every physics step from the contacts
+/// of the `Entity`s `PhysicsCollider` (or from its `CharacterController`, if
+/// present) and offers coyote time and jump buffering for platformer style
+/// jumping.
+///
+/// ```ignore
+/// if input_jump_pressed {
+///     ground_sensor.request_jump();
+/// }
+/// if ground_sensor.consume_jump() {
+///     physics_body.velocity.y = jump_velocity;
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct GroundSensor {
+    /// The up direction of the `Entity`, used to tell floors from walls.
+    pub up: Unit<Vector3<Float>>,
+    /// The steepest slope (in radians) that still counts as ground.
+    pub max_slope: Float,
+    /// The time (in seconds) after leaving the ground during which jumping is
+    /// still allowed.
+    pub coyote_time: Float,
+    /// The time (in seconds) a jump request is remembered while the `Entity`
+    /// can't jump yet.
+    pub jump_buffer_time: Float,
+    pub(crate) grounded: bool,
+    pub(crate) ground: Option<Entity>,
+    pub(crate) ground_normal: Option<Unit<Vector3<Float>>>,
+    pub(crate) ground_velocity: Vector3<Float>,
+    pub(crate) time_since_grounded: Option<Float>,
+    pub(crate) time_since_jump_request: Option<Float>,
+}
+
+impl Component for GroundSensor {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Default for GroundSensor {
+    fn default() -> Self {
+        Self {
+            up: Vector3::y_axis(),
+            max_slope: Float::from(std::f32::consts::FRAC_PI_4),
+            coyote_time: Float::from(0.1),
+            jump_buffer_time: Float::from(0.1),
+            grounded: false,
+            ground: None,
+            ground_normal: None,
+            ground_velocity: Vector3::zeros(),
+            time_since_grounded: None,
+            time_since_jump_request: None,
+        }
+    }
+}
+
+impl GroundSensor {
+    /// Sets the up direction of the `Entity`.
+    pub fn with_up(mut self, up: Unit<Vector3<Float>>) -> Self {
+        self.up = up;
+        self
+    }
+
+    /// Sets the steepest slope (in radians) that still counts as ground.
+    pub fn with_max_slope(mut self, max_slope: Float) -> Self {
+        self.max_slope = max_slope;
+        self
+    }
+
+    /// Sets the coyote time (in seconds).
+    pub fn with_coyote_time(mut self, coyote_time: Float) -> Self {
+        self.coyote_time = coyote_time;
+        self
+    }
+
+    /// Sets the jump buffer time (in seconds).
+    pub fn with_jump_buffer_time(mut self, jump_buffer_time: Float) -> Self {
+        self.jump_buffer_time = jump_buffer_time;
+        self
+    }
+
+    /// Checks whether the `Entity` currently stands on walkable ground.
+    pub fn grounded(&self) -> bool {
+        self.grounded
+    }
+
+    /// Returns the `Entity` the `Entity` currently stands on.
+    pub fn ground(&self) -> Option<Entity> {
+        self.ground
+    }
+
+    /// Returns the normal of the ground the `Entity` currently stands on.
+    pub fn ground_normal(&self) -> Option<Unit<Vector3<Float>>> {
+        self.ground_normal
+    }
+
+    /// Returns the linear velocity of the ground the `Entity` currently stands
+    /// on, e.g. of a moving platform.
+    pub fn ground_velocity(&self) -> Vector3<Float> {
+        self.ground_velocity
+    }
+
+    /// Checks whether the `Entity` is grounded or left the ground no longer
+    /// than `coyote_time` ago.
+    pub fn can_jump(&self) -> bool {
+        self.grounded
+            || self
+                .time_since_grounded
+                .map_or(false, |time| time <= self.coyote_time)
+    }
+
+    /// Requests a jump, which is remembered for `jump_buffer_time`.
+    pub fn request_jump(&mut self) {
+        self.time_since_jump_request = Some(Float::from(0.0));
+    }
+
+    /// Checks whether a jump was requested no longer than `jump_buffer_time`
+    /// ago and the `Entity` can jump. If so, the request and the coyote time
+    /// are consumed and `true` is returned.
+    pub fn consume_jump(&mut self) -> bool {
+        let requested = self
+            .time_since_jump_request
+            .map_or(false, |time| time <= self.jump_buffer_time);
+
+        if requested && self.can_jump() {
+            self.time_since_jump_request = None;
+            self.time_since_grounded = None;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Checks whether a surface with the given normal counts as ground.
+    pub(crate) fn is_walkable(&self, normal: &Vector3<Float>) -> bool {
+        normal.dot(&self.up) >= self.max_slope.cos()
+    }
+
+    /// Replaces the ground state and advances the coyote and jump buffer timers
+    /// by the given timestep.
+    pub(crate) fn update(
+        &mut self,
+        ground: Option<(Entity, Unit<Vector3<Float>>, Vector3<Float>)>,
+        timestep: Float,
+    ) {
+        match ground {
+            Some((entity, normal, velocity)) => {
+                self.grounded = true;
+                self.ground = Some(entity);
+                self.ground_normal = Some(normal);
+                self.ground_velocity = velocity;
+            }
+            None => {
+                self.grounded = false;
+                self.ground = None;
+                self.ground_normal = None;
+                self.ground_velocity = Vector3::zeros();
+            }
+        }
+
+        // the coyote timer runs from the last grounded step until a jump consumes it
+        self.time_since_grounded = if self.grounded {
+            Some(Float::from(0.0))
+        } else {
+            self.time_since_grounded.map(|time| time + timestep)
+        };
+
+        self.time_since_jump_request = self.time_since_jump_request.map(|time| time + timestep);
+    }
+}
+
+/// The `OneWayPlatform` lets colliders pass through an `Entity`s
+/// `PhysicsCollider` in the given `direction`, while colliders on its far side
+/// are blocked as usual, e.g. platforms that can be jumped onto from below.
+/// Contacts whose normal points against the pass-through `direction`, e.g.
+/// from below or the side, are ignored until the collider separated from the
+/// platform again. The `PhysicsBundle` takes over the broad phase pair filter
+/// of the physics world for this; custom filters have to be chained through
+/// `PhysicsBundle::with_broad_phase_pair_filter`.
+#[derive(Clone, Debug)]
+pub struct OneWayPlatform {
+    /// The direction colliders can pass through the platform in, relative to
+    /// the rotation of the `PhysicsCollider`.
+    pub direction: Unit<Vector3<Float>>,
+    /// The distance a collider may sink into the platform and still be
+    /// considered on top of it. Contacts are detected up to this distance, so
+    /// it has to exceed the skin width of `CharacterController`s.
+    pub tolerance: Float,
+}
+
+impl Component for OneWayPlatform {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Default for OneWayPlatform {
+    fn default() -> Self {
+        Self {
+            direction: Vector3::y_axis(),
+            tolerance: Float::from(0.1),
+        }
+    }
+}
+
+impl OneWayPlatform {
+    /// Creates a new `OneWayPlatform` that can be passed through in the given
+    /// direction.
+    pub fn new(direction: Unit<Vector3<Float>>) -> Self {
+        Self {
+            direction,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the distance a collider may sink into the platform and still be
+    /// considered on top of it.
+    pub fn with_tolerance(mut self, tolerance: Float) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+}
+
+/// Inserting the `DropThrough` `Component` lets an `Entity` drop through the
+/// `OneWayPlatform`s it currently stands on. It is removed by the
+/// `PhysicsBundle` once the `Entity` left all `OneWayPlatform`s.
+#[derive(Default)]
+pub struct DropThrough;
+
+impl Component for DropThrough {
+    type Storage = NullStorage<Self>;
+}
+
+/// The `OneWayPlatformPairs` resource contains all pairs of `OneWayPlatform`
+/// and other `Entity`s that currently don't collide with each other.
+#[derive(Default)]
+pub(crate) struct OneWayPlatformPairs {
+    pub(crate) pairs: HashSet<(Entity, Entity)>,
+}
+
+impl OneWayPlatformPairs {
+    /// Returns the `OneWayPlatform`s the given `Entity` currently passes
+    /// through.
+    pub(crate) fn platforms(&self, entity: Entity) -> impl Iterator<Item = Entity> + '_ {
+        self.pairs
+            .iter()
+            .filter(move |(_, other)| *other == entity)
+            .map(|(platform, _)| *platform)
+    }
+}
+
+/// The `PlatformPath` moves a kinematic `PhysicsBody` along a list of
+/// waypoints, e.g. for moving platforms and elevators. The `PhysicsBody` starts
+/// at the first waypoint and travels with the given `speed`; riding `Entity`s
+/// inherit its velocity through `PlatformRider`s and `CharacterController`s.
+#[derive(Clone, Debug)]
+pub struct PlatformPath {
+    /// The world-space positions the `PhysicsBody` travels between.
+    pub waypoints: Vec<Vector3<Float>>,
+    /// The speed (in units per second) the `PhysicsBody` travels with.
+    pub speed: Float,
+    /// Whether the path is travelled back and forth or in a loop.
+    pub mode: PathMode,
+    /// The easing applied between two waypoints.
+    pub easing: PathEasing,
+    segment: usize,
+    progress: Float,
+    reverse: bool,
+}
+
+impl Component for PlatformPath {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl PlatformPath {
+    /// Creates a new `PlatformPath` along the given waypoints.
+    pub fn new(waypoints: Vec<Vector3<Float>>, speed: Float) -> Self {
+        Self {
+            waypoints,
+            speed,
+            mode: PathMode::default(),
+            easing: PathEasing::default(),
+            segment: 0,
+            progress: Float::from(0.0),
+            reverse: false,
+        }
+    }
+
+    /// Sets whether the path is travelled back and forth or in a loop.
+    pub fn with_mode(mut self, mode: PathMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Sets the easing applied between two waypoints.
+    pub fn with_easing(mut self, easing: PathEasing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    /// Returns the index of the waypoint the `PhysicsBody` is currently
+    /// travelling to.
+    pub fn target(&self) -> Option<usize> {
+        if self.waypoints.len() < 2 || self.segment >= self.waypoints.len() {
+            None
+        } else {
+            Some(self.next(self.segment)).filter(|target| *target < self.waypoints.len())
+        }
+    }
+
+    /// Travels the given distance along the path and returns the new position.
+    pub(crate) fn advance(&mut self, distance: Float) -> Option<Vector3<Float>> {
+        match self.waypoints.len() {
+            0 => return None,
+            1 => return Some(self.waypoints[0]),
+            _ => {}
+        }
+
+        // the waypoints or the mode may have been replaced since the last step
+        if self.segment >= self.waypoints.len() {
+            self.segment = 0;
+            self.progress = Float::from(0.0);
+        }
+        self.turn_around();
+
+        // zero length segments are skipped, but at most once per waypoint
+        let mut remaining = distance;
+        for _ in 0..=self.waypoints.len() {
+            let length = self.segment_length();
+            if self.progress + remaining < length {
+                self.progress += remaining;
+                break;
+            }
+
+            remaining -= length - self.progress;
+            self.progress = Float::from(0.0);
+            self.segment = self.next(self.segment);
+            self.turn_around();
+        }
+
+        let from = self.waypoints[self.segment];
+        let to = self.waypoints[self.next(self.segment)];
+        let length = self.segment_length();
+        let t = if length > Float::from(0.0) {
+            self.progress / length
+        } else {
+            Float::from(1.0)
+        };
+
+        Some(from + (to - from) * self.easing.apply(t))
+    }
+
+    /// Reverses the direction of `PathMode::PingPong` paths at their ends.
+    fn turn_around(&mut self) {
+        if self.mode == PathMode::PingPong {
+            if self.segment == self.waypoints.len() - 1 {
+                self.reverse = true;
+            } else if self.segment == 0 {
+                self.reverse = false;
+            }
+        }
+    }
+
+    fn segment_length(&self) -> Float {
+        (self.waypoints[self.next(self.segment)] - self.waypoints[self.segment]).norm()
+    }
+
+    fn next(&self, segment: usize) -> usize {
+        match self.mode {
+            PathMode::Loop => (segment + 1) % self.waypoints.len(),
+            PathMode::PingPong if self.reverse => segment.saturating_sub(1),
+            PathMode::PingPong => segment + 1,
+        }
+    }
+}
+
+/// The `PathMode` defines how a `PlatformPath` continues after its last
+/// waypoint.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PathMode {
+    /// Travels the waypoints back in reverse order.
+    PingPong,
+    /// Travels from the last waypoint back to the first one.
+    Loop,
+}
+
+impl Default for PathMode {
+    fn default() -> Self {
+        PathMode::PingPong
+    }
+}
+
+/// The `PathEasing` defines how a `PlatformPath` accelerates and decelerates
+/// between two waypoints.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PathEasing {
+    /// Travels with constant speed.
+    Linear,
+    /// Accelerates after and decelerates before every waypoint; `speed` is the
+    /// average speed.
+    SmoothStep,
+}
+
+impl Default for PathEasing {
+    fn default() -> Self {
+        PathEasing::Linear
+    }
+}
+
+impl PathEasing {
+    /// Maps the linear progress `t` in `[0, 1]` between two waypoints to the
+    /// eased progress.
+    fn apply(self, t: Float) -> Float {
+        match self {
+            PathEasing::Linear => t,
+            PathEasing::SmoothStep => t * t * (Float::from(3.0) - Float::from(2.0) * t),
+        }
+    }
+}
+
+/// The `PlatformRider` lets a dynamic `PhysicsBody` with a `GroundSensor`
+/// inherit the velocity of the ground it stands on, so it is carried by moving
+/// platforms instead of sliding off. The inherited velocity is kept as momentum
+/// when leaving the ground.
+#[derive(Clone, Debug, Default)]
+pub struct PlatformRider {
+    pub(crate) inherited_velocity: Vector3<Float>,
+}
+
+impl Component for PlatformRider {
+    type Storage = DenseVecStorage<Self>;
+}
@@ -0,0 +1,243 @@
+use amethyst::{
+    assets::PrefabData,
+    core::{
+        math::{Isometry3, Translation3, UnitQuaternion, Vector3},
+        Float,
+    },
+    ecs::{Entity, WriteStorage},
+    error::Error,
+};
+use serde::{Deserialize, Serialize};
+
+use specs_physics::{
+    bodies::BodyStatus,
+    colliders::{PhysicsCollider, Shape},
+    ncollide::world::CollisionGroups,
+    nphysics::material::{BasicMaterial, MaterialHandle},
+    PhysicsBody,
+    PhysicsBodyBuilder,
+    PhysicsColliderBuilder,
+};
+
+/// `PrefabData` for `PhysicsBody`s. All fields are optional and default to the
+/// values of the `PhysicsBodyBuilder`, except for the `status` which defaults
+/// to `BodyStatusPrefab::Dynamic`.
+///
+/// ```ron
+/// body: (
+///     status: Dynamic,
+///     mass: 1.0,
+///     gravity_enabled: false,
+/// ),
+/// ```
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PhysicsBodyPrefab {
+    /// The `BodyStatus` of the `PhysicsBody`.
+    pub status: BodyStatusPrefab,
+    /// Whether gravity affects the `PhysicsBody`.
+    pub gravity_enabled: Option<bool>,
+    /// The mass of the `PhysicsBody`.
+    pub mass: Option<f32>,
+    /// The initial linear velocity of the `PhysicsBody`.
+    pub velocity: Option<[f32; 3]>,
+}
+
+impl<'a> PrefabData<'a> for PhysicsBodyPrefab {
+    type Result = ();
+    type SystemData = WriteStorage<'a, PhysicsBody<Float>>;
+
+    fn add_to_entity(
+        &self,
+        entity: Entity,
+        physics_bodies: &mut Self::SystemData,
+        _: &[Entity],
+        _: &[Entity],
+    ) -> Result<(), Error> {
+        let mut builder = PhysicsBodyBuilder::<Float>::from(BodyStatus::from(self.status));
+        if let Some(gravity_enabled) = self.gravity_enabled {
+            builder = builder.gravity_enabled(gravity_enabled);
+        }
+        if let Some(mass) = self.mass {
+            builder = builder.mass(mass.into());
+        }
+        if let Some([x, y, z]) = self.velocity {
+            builder = builder.velocity(Vector3::new(x.into(), y.into(), z.into()));
+        }
+
+        physics_bodies.insert(entity, builder.build())?;
+        Ok(())
+    }
+}
+
+/// Serializable counterpart of the nphysics `BodyStatus`.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub enum BodyStatusPrefab {
+    /// The body is affected by forces and collisions.
+    Dynamic,
+    /// The body never moves.
+    Static,
+    /// The body is only moved by its velocity and ignores forces.
+    Kinematic,
+    /// The body is ignored by the physics world.
+    Disabled,
+}
+
+impl Default for BodyStatusPrefab {
+    fn default() -> Self {
+        BodyStatusPrefab::Dynamic
+    }
+}
+
+impl From<BodyStatusPrefab> for BodyStatus {
+    fn from(status: BodyStatusPrefab) -> Self {
+        match status {
+            BodyStatusPrefab::Dynamic => BodyStatus::Dynamic,
+            BodyStatusPrefab::Static => BodyStatus::Static,
+            BodyStatusPrefab::Kinematic => BodyStatus::Kinematic,
+            BodyStatusPrefab::Disabled => BodyStatus::Disabled,
+        }
+    }
+}
+
+/// `PrefabData` for `PhysicsCollider`s. Apart from the `shape`, all fields are
+/// optional and default to the values of the `PhysicsColliderBuilder`.
+///
+/// ```ron
+/// collider: (
+///     shape: Rectangle(width: 15.0, height: 16.0, depth: 1.0),
+///     sensor: false,
+///     material: (restitution: 0.0, friction: 0.5),
+///     groups: (membership: [1], whitelist: [0, 2]),
+/// ),
+/// ```
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PhysicsColliderPrefab {
+    /// The `Shape` of the `PhysicsCollider`.
+    pub shape: ShapePrefab,
+    /// Whether the `PhysicsCollider` is a sensor.
+    #[serde(default)]
+    pub sensor: bool,
+    /// The offset (translation) of the `PhysicsCollider` from its parent.
+    #[serde(default)]
+    pub offset: Option<[f32; 3]>,
+    /// The density of the `PhysicsCollider`, used to compute the mass of its
+    /// parent.
+    #[serde(default)]
+    pub density: Option<f32>,
+    /// The margin of the `PhysicsCollider`.
+    #[serde(default)]
+    pub margin: Option<f32>,
+    /// The material of the `PhysicsCollider`.
+    #[serde(default)]
+    pub material: Option<MaterialPrefab>,
+    /// The collision groups of the `PhysicsCollider`.
+    #[serde(default)]
+    pub groups: Option<CollisionGroupsPrefab>,
+}
+
+impl<'a> PrefabData<'a> for PhysicsColliderPrefab {
+    type Result = ();
+    type SystemData = WriteStorage<'a, PhysicsCollider<Float>>;
+
+    fn add_to_entity(
+        &self,
+        entity: Entity,
+        physics_colliders: &mut Self::SystemData,
+        _: &[Entity],
+        _: &[Entity],
+    ) -> Result<(), Error> {
+        let mut builder =
+            PhysicsColliderBuilder::<Float>::from(Shape::from(self.shape)).sensor(self.sensor);
+        if let Some([x, y, z]) = self.offset {
+            builder = builder.offset_from_parent(Isometry3::from_parts(
+                Translation3::new(x.into(), y.into(), z.into()),
+                UnitQuaternion::identity(),
+            ));
+        }
+        if let Some(density) = self.density {
+            builder = builder.density(density.into());
+        }
+        if let Some(margin) = self.margin {
+            builder = builder.margin(margin.into());
+        }
+        if let Some(material) = self.material {
+            builder = builder.material(MaterialHandle::from(material));
+        }
+        if let Some(ref groups) = self.groups {
+            builder = builder.collision_groups(CollisionGroups::from(groups));
+        }
+
+        physics_colliders.insert(entity, builder.build())?;
+        Ok(())
+    }
+}
+
+/// Serializable counterpart of the `specs_physics` `Shape`.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub enum ShapePrefab {
+    /// A circle (ball) with the given radius.
+    Circle { radius: f32 },
+    /// A rectangle (cuboid) with the given dimensions.
+    Rectangle { width: f32, height: f32, depth: f32 },
+}
+
+impl From<ShapePrefab> for Shape<Float> {
+    fn from(shape: ShapePrefab) -> Self {
+        match shape {
+            ShapePrefab::Circle { radius } => Shape::Circle(radius.into()),
+            ShapePrefab::Rectangle {
+                width,
+                height,
+                depth,
+            } => Shape::Rectangle(width.into(), height.into(), depth.into()),
+        }
+    }
+}
+
+/// Serializable counterpart of the nphysics `BasicMaterial`.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct MaterialPrefab {
+    /// The restitution (bounciness) coefficient.
+    pub restitution: f32,
+    /// The friction coefficient.
+    pub friction: f32,
+}
+
+impl From<MaterialPrefab> for MaterialHandle<Float> {
+    fn from(material: MaterialPrefab) -> Self {
+        MaterialHandle::new(BasicMaterial::new(
+            material.restitution.into(),
+            material.friction.into(),
+        ))
+    }
+}
+
+/// Serializable counterpart of the ncollide `CollisionGroups`. Omitted fields
+/// keep the defaults of `CollisionGroups::new()`.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct CollisionGroupsPrefab {
+    /// The groups the collider is a member of.
+    pub membership: Option<Vec<usize>>,
+    /// The groups the collider can interact with.
+    pub whitelist: Option<Vec<usize>>,
+    /// The groups the collider can not interact with.
+    pub blacklist: Option<Vec<usize>>,
+}
+
+impl<'a> From<&'a CollisionGroupsPrefab> for CollisionGroups {
+    fn from(groups: &'a CollisionGroupsPrefab) -> Self {
+        let mut collision_groups = CollisionGroups::new();
+        if let Some(ref membership) = groups.membership {
+            collision_groups = collision_groups.with_membership(membership);
+        }
+        if let Some(ref whitelist) = groups.whitelist {
+            collision_groups = collision_groups.with_whitelist(whitelist);
+        }
+        if let Some(ref blacklist) = groups.blacklist {
+            collision_groups = collision_groups.with_blacklist(blacklist);
+        }
+        collision_groups
+    }
+}
@@ -0,0 +1,404 @@
+use std::cmp::Ordering;
+
+use amethyst::{
+    core::{
+        math::{Isometry3, Point2, Point3, Translation3, Unit, Vector3},
+        transform::Transform,
+        Float,
+    },
+    ecs::{shred::ResourceId, Entity, ReadExpect, Resources, SystemData},
+    renderer::Camera,
+    window::ScreenDimensions,
+};
+
+use specs_physics::{
+    colliders::Shape,
+    ncollide::{
+        bounding_volume::{BoundingVolume, AABB},
+        query::{self, Proximity},
+        shape::ShapeHandle,
+    },
+    nphysics::object::Collider,
+    Physics,
+};
+
+pub use specs_physics::ncollide::{query::Ray, world::CollisionGroups};
+
+use crate::collider_entity;
+
+/// The `PhysicsQuery` is a `SystemData` that offers read-only spatial queries
+/// against the physics world, e.g. for line-of-sight checks or hitscan weapons.
+///
+/// ```ignore
+/// impl<'s> System<'s> for LineOfSightSystem {
+///     type SystemData = PhysicsQuery<'s>;
+///
+///     fn run(&mut self, query: Self::SystemData) {
+///         let ray = Ray::new(origin, direction);
+///         if let Some(hit) = query.cast_ray(&ray, 100.0.into(), &QueryFilter::default()) {
+///             // ...
+///         }
+///     }
+/// }
+/// ```
+pub struct PhysicsQuery<'a> {
+    physics: ReadExpect<'a, Physics<Float>>,
+}
+
+impl<'a> SystemData<'a> for PhysicsQuery<'a> {
+    fn setup(res: &mut Resources) {
+        <ReadExpect<'a, Physics<Float>> as SystemData<'a>>::setup(res);
+    }
+
+    fn fetch(res: &'a Resources) -> Self {
+        Self {
+            physics: SystemData::fetch(res),
+        }
+    }
+
+    fn reads() -> Vec<ResourceId> {
+        <ReadExpect<'a, Physics<Float>> as SystemData<'a>>::reads()
+    }
+
+    fn writes() -> Vec<ResourceId> {
+        <ReadExpect<'a, Physics<Float>> as SystemData<'a>>::writes()
+    }
+}
+
+impl<'a> PhysicsQuery<'a> {
+    /// Casts the given `Ray` into the physics world and returns the first hit
+    /// within `max_toi` that is accepted by the `QueryFilter`. The time of
+    /// impact is expressed in multiples of the `Ray` direction.
+    pub fn cast_ray(
+        &self,
+        ray: &Ray<Float>,
+        max_toi: Float,
+        filter: &QueryFilter,
+    ) -> Option<RaycastHit> {
+        self.ray_hits(ray, max_toi, filter)
+            .min_by(|a, b| a.toi.partial_cmp(&b.toi).unwrap_or(Ordering::Equal))
+    }
+
+    /// Casts the given `Ray` into the physics world and returns all hits within
+    /// `max_toi` that are accepted by the `QueryFilter`, sorted by their time
+    /// of impact.
+    pub fn cast_ray_all(
+        &self,
+        ray: &Ray<Float>,
+        max_toi: Float,
+        filter: &QueryFilter,
+    ) -> Vec<RaycastHit> {
+        let mut hits = self.ray_hits(ray, max_toi, filter).collect::<Vec<_>>();
+        hits.sort_by(|a, b| a.toi.partial_cmp(&b.toi).unwrap_or(Ordering::Equal));
+        hits
+    }
+
+    /// Sweeps the given `Shape` from the `start` pose along `translation` and
+    /// returns the first collider it hits that is accepted by the
+    /// `QueryFilter`. The time of impact is expressed as fraction of
+    /// `translation`. All other colliders are considered to be stationary.
+    pub fn cast_shape(
+        &self,
+        shape: &Shape<Float>,
+        start: &Isometry3<Float>,
+        translation: &Vector3<Float>,
+        filter: &QueryFilter,
+    ) -> Option<ShapeCastHit> {
+        self.shape_hits(shape, start, translation, filter)
+            .into_iter()
+            .min_by(|a, b| a.toi.partial_cmp(&b.toi).unwrap_or(Ordering::Equal))
+    }
+
+    /// Sweeps the given `Shape` from the `start` pose along `translation` and
+    /// returns all colliders it hits that are accepted by the `QueryFilter`,
+    /// sorted by their time of impact.
+    pub fn cast_shape_all(
+        &self,
+        shape: &Shape<Float>,
+        start: &Isometry3<Float>,
+        translation: &Vector3<Float>,
+        filter: &QueryFilter,
+    ) -> Vec<ShapeCastHit> {
+        let mut hits = self.shape_hits(shape, start, translation, filter);
+        hits.sort_by(|a, b| a.toi.partial_cmp(&b.toi).unwrap_or(Ordering::Equal));
+        hits
+    }
+
+    /// Returns the `Entity`s of all colliders that overlap the given `Shape` at
+    /// the given pose and are accepted by the `QueryFilter`.
+    pub fn overlaps(
+        &self,
+        shape: &Shape<Float>,
+        isometry: &Isometry3<Float>,
+        filter: &QueryFilter,
+    ) -> Vec<Entity> {
+        let shape_handle = shape_handle(shape);
+        let aabb = shape_handle.aabb(isometry);
+
+        let mut entities = Vec::new();
+        for collider in self
+            .physics
+            .world()
+            .collider_world()
+            .interferences_with_aabb(&aabb, &filter.groups)
+        {
+            let entity = match filter.entity(collider) {
+                Some(entity) => entity,
+                None => continue,
+            };
+
+            let proximity = query::proximity(
+                isometry,
+                shape_handle.as_ref(),
+                collider.position(),
+                collider.shape().as_ref(),
+                Float::from(0.0),
+            );
+
+            if proximity == Proximity::Intersecting && !entities.contains(&entity) {
+                entities.push(entity);
+            }
+        }
+
+        entities
+    }
+
+    /// Returns the `Entity`s of all colliders that contain the given
+    /// world-space point and are accepted by the `QueryFilter`.
+    pub fn colliders_at_point(&self, point: &Point3<Float>, filter: &QueryFilter) -> Vec<Entity> {
+        let mut entities = Vec::new();
+        for collider in self
+            .physics
+            .world()
+            .collider_world()
+            .interferences_with_point(point, &filter.groups)
+        {
+            if let Some(entity) = filter.entity(collider) {
+                if !entities.contains(&entity) {
+                    entities.push(entity);
+                }
+            }
+        }
+
+        entities
+    }
+
+    /// Returns the `Entity` of the first collider that contains the given
+    /// world-space point and is accepted by the `QueryFilter`.
+    pub fn pick_point(&self, point: &Point3<Float>, filter: &QueryFilter) -> Option<Entity> {
+        self.colliders_at_point(point, filter).into_iter().next()
+    }
+
+    /// Returns the `Entity` of the first collider hit by the given `Ray` that
+    /// is accepted by the `QueryFilter`.
+    pub fn pick(&self, ray: &Ray<Float>, filter: &QueryFilter) -> Option<Entity> {
+        self.cast_ray(ray, Float::from(std::f32::MAX), filter)
+            .map(|hit| hit.entity)
+    }
+
+    /// Returns the `Entity` of the first collider under the given screen-space
+    /// position (e.g. the mouse position), as seen through the given `Camera`.
+    /// See `screen_ray` for details.
+    pub fn pick_screen_position(
+        &self,
+        screen_position: Point2<f32>,
+        camera: &Camera,
+        camera_transform: &Transform,
+        screen_dimensions: &ScreenDimensions,
+        filter: &QueryFilter,
+    ) -> Option<Entity> {
+        screen_ray(screen_position, camera, camera_transform, screen_dimensions)
+            .and_then(|ray| self.pick(&ray, filter))
+    }
+
+    fn shape_hits(
+        &self,
+        shape: &Shape<Float>,
+        start: &Isometry3<Float>,
+        translation: &Vector3<Float>,
+        filter: &QueryFilter,
+    ) -> Vec<ShapeCastHit> {
+        let shape_handle = shape_handle(shape);
+        let end = Translation3::from(*translation) * start;
+        let aabb: AABB<Float> = shape_handle.aabb(start).merged(&shape_handle.aabb(&end));
+
+        // at the time of impact both shapes touch, the distance between them can
+        // never exceed the length of the sweep
+        let prediction = translation.norm() + Float::from(0.001);
+        let zero = Vector3::zeros();
+
+        let mut hits = Vec::new();
+        for collider in self
+            .physics
+            .world()
+            .collider_world()
+            .interferences_with_aabb(&aabb, &filter.groups)
+        {
+            let entity = match filter.entity(collider) {
+                Some(entity) => entity,
+                None => continue,
+            };
+
+            let toi = match query::time_of_impact(
+                start,
+                translation,
+                shape_handle.as_ref(),
+                collider.position(),
+                &zero,
+                collider.shape().as_ref(),
+            ) {
+                Some(toi) if toi <= Float::from(1.0) => toi,
+                _ => continue,
+            };
+
+            // compute the contact at the time of impact for the hit normal and point
+            let impact = Translation3::from(translation * toi) * start;
+            let contact = query::contact(
+                &impact,
+                shape_handle.as_ref(),
+                collider.position(),
+                collider.shape().as_ref(),
+                prediction,
+            );
+
+            hits.push(ShapeCastHit {
+                entity,
+                toi,
+                point: contact.as_ref().map(|contact| contact.world2),
+                normal: contact.map(|contact| -contact.normal),
+            });
+        }
+
+        hits
+    }
+
+    fn ray_hits<'b>(
+        &'b self,
+        ray: &'b Ray<Float>,
+        max_toi: Float,
+        filter: &'b QueryFilter,
+    ) -> impl Iterator<Item = RaycastHit> + 'b {
+        self.physics
+            .world()
+            .collider_world()
+            .interferences_with_ray(ray, &filter.groups)
+            .filter(move |(_, intersection)| intersection.toi <= max_toi)
+            .filter_map(move |(collider, intersection)| {
+                filter.entity(collider).map(|entity| RaycastHit {
+                    entity,
+                    point: ray.point_at(intersection.toi),
+                    normal: intersection.normal,
+                    toi: intersection.toi,
+                })
+            })
+    }
+}
+
+/// The `RaycastHit` describes where a `Ray` hit a collider.
+#[derive(Clone, Debug)]
+pub struct RaycastHit {
+    /// The `Entity` of the collider that was hit.
+    pub entity: Entity,
+    /// The world-space point the `Ray` hit the collider at.
+    pub point: Point3<Float>,
+    /// The world-space surface normal at the hit point.
+    pub normal: Vector3<Float>,
+    /// The time of impact, expressed in multiples of the `Ray` direction.
+    pub toi: Float,
+}
+
+/// The `ShapeCastHit` describes where a swept `Shape` hit a collider.
+#[derive(Clone, Debug)]
+pub struct ShapeCastHit {
+    /// The `Entity` of the collider that was hit.
+    pub entity: Entity,
+    /// The time of impact, expressed as fraction of the sweep translation.
+    pub toi: Float,
+    /// The world-space point on the hit collider at the time of impact.
+    pub point: Option<Point3<Float>>,
+    /// The world-space surface normal of the hit collider at the time of
+    /// impact, pointing towards the swept `Shape`.
+    pub normal: Option<Unit<Vector3<Float>>>,
+}
+
+/// The `QueryFilter` restricts the colliders considered by `PhysicsQuery`
+/// queries. By default, all solid colliders are considered while sensors are
+/// ignored.
+#[derive(Clone, Debug)]
+pub struct QueryFilter {
+    groups: CollisionGroups,
+    excluded: Vec<Entity>,
+    sensors: bool,
+}
+
+impl Default for QueryFilter {
+    fn default() -> Self {
+        Self {
+            groups: CollisionGroups::new(),
+            excluded: Vec::new(),
+            sensors: false,
+        }
+    }
+}
+
+impl QueryFilter {
+    /// Only considers colliders that can interact with the given
+    /// `CollisionGroups`.
+    pub fn with_groups(mut self, groups: CollisionGroups) -> Self {
+        self.groups = groups;
+        self
+    }
+
+    /// Ignores the colliders of the given `Entity`, e.g. the `Entity` the query
+    /// originates from.
+    pub fn excluding(mut self, entity: Entity) -> Self {
+        self.excluded.push(entity);
+        self
+    }
+
+    /// Also considers sensor colliders.
+    pub fn with_sensors(mut self) -> Self {
+        self.sensors = true;
+        self
+    }
+
+    /// Returns the `Entity` of the given `Collider` if it passes this filter.
+    fn entity(&self, collider: &Collider<Float>) -> Option<Entity> {
+        if collider.is_sensor() && !self.sensors {
+            return None;
+        }
+
+        collider_entity(collider).filter(|entity| !self.excluded.contains(entity))
+    }
+}
+
+/// Converts a screen-space position (in pixels, with the origin in the top left
+/// corner, e.g. the mouse position) into a world-space `Ray` through the given
+/// `Camera`. The `Ray` starts at the near plane and reaches the far plane at a
+/// time of impact of `1`. Returns `None` if the `Camera` projection cannot be
+/// inverted.
+pub fn screen_ray(
+    screen_position: Point2<f32>,
+    camera: &Camera,
+    camera_transform: &Transform,
+    screen_dimensions: &ScreenDimensions,
+) -> Option<Ray<Float>> {
+    // normalised device coordinates
+    let x = 2.0 * screen_position.x / screen_dimensions.width() - 1.0;
+    let y = 2.0 * screen_position.y / screen_dimensions.height() - 1.0;
+
+    let matrix =
+        camera_transform.global_matrix().map(|c| c.as_f32()) * camera.proj.try_inverse()?;
+    let near = matrix.transform_point(&Point3::new(x, y, 0.0));
+    let far = matrix.transform_point(&Point3::new(x, y, 1.0));
+
+    Some(Ray::new(
+        Point3::from(near.coords.map(Float::from)),
+        (far - near).map(Float::from),
+    ))
+}
+
+/// Converts a `specs_physics` `Shape` into the `ShapeHandle` used for queries.
+fn shape_handle(shape: &Shape<Float>) -> ShapeHandle<Float> {
+    shape.handle(Float::from(0.0))
+}
@@ -0,0 +1,319 @@
+use std::collections::HashMap;
+
+use amethyst::{
+    core::{
+        math::{Isometry3, Unit, Vector3},
+        Float,
+    },
+    ecs::{
+        Entities,
+        Join,
+        Read,
+        ReadExpect,
+        ReadStorage,
+        Resources,
+        System,
+        SystemData,
+        WriteStorage,
+    },
+};
+
+use specs_physics::{
+    bodies::{PhysicsBody, Position},
+    colliders::{PhysicsCollider, Shape},
+    Physics,
+};
+
+use super::one_way_platforms::{passes_through, platform_up};
+use crate::{
+    character::CharacterController,
+    platformer::{OneWayPlatform, OneWayPlatformPairs},
+    query::{PhysicsQuery, QueryFilter, ShapeCastHit},
+    PhysicsTransform,
+};
+
+/// The `CharacterControllerSystem` resolves the `desired_velocity` of every
+/// `CharacterController` into collision free movement by sweeping its
+/// `PhysicsCollider` shape through the physics world. The resulting
+/// displacement and the velocity of the ground are applied as velocity of the
+/// (kinematic) `PhysicsBody`, which is moved by the next physics step.
+#[derive(Default)]
+pub struct CharacterControllerSystem;
+
+impl<'s> System<'s> for CharacterControllerSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadExpect<'s, Physics<Float>>,
+        PhysicsQuery<'s>,
+        Read<'s, OneWayPlatformPairs>,
+        ReadStorage<'s, OneWayPlatform>,
+        ReadStorage<'s, PhysicsTransform>,
+        ReadStorage<'s, PhysicsCollider<Float>>,
+        WriteStorage<'s, PhysicsBody<Float>>,
+        WriteStorage<'s, CharacterController>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            physics,
+            query,
+            one_way_platform_pairs,
+            one_way_platforms,
+            physics_transforms,
+            physics_colliders,
+            mut physics_bodies,
+            mut character_controllers,
+        ) = data;
+
+        let timestep = physics.world().timestep();
+
+        // the world-space pass-through directions of all OneWayPlatforms
+        let platform_ups = (&entities, &one_way_platforms, &physics_colliders)
+            .join()
+            .filter_map(|(entity, platform, physics_collider)| {
+                physics_collider
+                    .handle()
+                    .and_then(|handle| physics.world().collider(handle))
+                    .map(|collider| (entity, platform_up(collider, platform)))
+            })
+            .collect::<HashMap<_, _>>();
+
+        // the PhysicsBodies of the ground already contain the upcoming velocity,
+        // e.g. of PlatformPaths
+        let ground_velocities = (&character_controllers)
+            .join()
+            .filter_map(|controller| controller.ground)
+            .filter_map(|ground| {
+                physics_bodies
+                    .get(ground)
+                    .map(|physics_body| (ground, physics_body.velocity))
+            })
+            .collect::<HashMap<_, _>>();
+
+        for (entity, physics_transform, physics_collider, physics_body, controller) in (
+            &entities,
+            &physics_transforms,
+            &physics_colliders,
+            &mut physics_bodies,
+            &mut character_controllers,
+        )
+            .join()
+        {
+            // OneWayPlatforms the character currently passes through are ignored;
+            // all others only block the character on their far side
+            let filter = one_way_platform_pairs.platforms(entity).fold(
+                QueryFilter::default()
+                    .with_groups(physics_collider.collision_groups)
+                    .excluding(entity),
+                |filter, platform| filter.excluding(platform),
+            );
+
+            let sweep = Sweep {
+                query: &query,
+                shape: &physics_collider.shape,
+                filter,
+                platform_ups: &platform_ups,
+            };
+
+            let start = physics_transform.isometry() * physics_collider.offset_from_parent;
+            let mut pose = start;
+
+            // the ground is only known after moving, steps can only be taken from it
+            let was_grounded = controller.grounded;
+            let ground_velocity = controller
+                .ground
+                .and_then(|ground| ground_velocities.get(&ground))
+                .cloned()
+                .unwrap_or_else(Vector3::zeros);
+            controller.grounded = false;
+            controller.ground_normal = None;
+            controller.ground = None;
+            controller.touching_wall = false;
+
+            // move along the ground first, then up or down
+            let up = controller.up.into_inner();
+            let motion = controller.desired_velocity * timestep;
+            let vertical = up * motion.dot(&up);
+            let horizontal = motion - vertical;
+
+            sweep.slide(controller, &mut pose, horizontal, was_grounded);
+            sweep.slide(controller, &mut pose, vertical, false);
+
+            // characters that rest on the ground don't move towards it
+            if !controller.grounded && motion.dot(&up) <= Float::from(0.0) {
+                sweep.probe_ground(controller, &pose);
+            }
+
+            // the character moves along with its ground, which is not swept as both
+            // move by the same distance during the physics step
+            physics_body.velocity =
+                (pose.translation.vector - start.translation.vector) / timestep + ground_velocity;
+        }
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        info!("CharacterControllerSystem.setup");
+        Self::SystemData::setup(res);
+    }
+}
+
+/// The `Sweep` moves the shape of a single `CharacterController` through the
+/// physics world.
+struct Sweep<'a, 'b> {
+    query: &'a PhysicsQuery<'b>,
+    shape: &'a Shape<Float>,
+    filter: QueryFilter,
+    platform_ups: &'a HashMap<Entity, Vector3<Float>>,
+}
+
+impl<'a, 'b> Sweep<'a, 'b> {
+    /// Moves the pose along the translation and slides along every surface it
+    /// hits, updating the ground and wall state of the `CharacterController`.
+    fn slide(
+        &self,
+        controller: &mut CharacterController,
+        pose: &mut Isometry3<Float>,
+        translation: Vector3<Float>,
+        can_step: bool,
+    ) {
+        let up = controller.up.into_inner();
+        let mut remaining = translation;
+
+        for _ in 0..controller.max_slides {
+            let (leftover, hit) = match self.cast(pose, &remaining, controller.skin_width) {
+                Some(result) => result,
+                None => break,
+            };
+
+            let normal = hit
+                .normal
+                .map_or_else(|| -remaining.normalize(), |normal| normal.into_inner());
+            if controller.is_walkable(&normal) {
+                controller.grounded = true;
+                controller.ground_normal = Some(Unit::new_normalize(normal));
+                controller.ground = Some(hit.entity);
+            } else if normal.dot(&up).abs() < controller.max_slope.cos() {
+                // try to step onto low obstacles before treating them as walls
+                if can_step && controller.step_height > Float::from(0.0) {
+                    if let Some(stepped) = self.step(controller, pose, &leftover) {
+                        *pose = stepped;
+                        break;
+                    }
+                }
+                controller.touching_wall = true;
+            }
+
+            // slide along the surface; walls and ceilings must not lift the
+            // character
+            remaining = leftover - normal * leftover.dot(&normal);
+            if !controller.is_walkable(&normal) {
+                let lift = remaining.dot(&up);
+                if lift > Float::from(0.0) && translation.dot(&up) <= Float::from(0.0) {
+                    remaining -= up * lift;
+                }
+            }
+        }
+    }
+
+    /// Tries to move the pose up by the step height, along the translation and
+    /// back down onto a walkable surface. Returns the resulting pose if the
+    /// character made progress.
+    fn step(
+        &self,
+        controller: &CharacterController,
+        pose: &Isometry3<Float>,
+        translation: &Vector3<Float>,
+    ) -> Option<Isometry3<Float>> {
+        let skin_width = controller.skin_width;
+        let mut stepped = *pose;
+
+        self.cast(
+            &mut stepped,
+            &(controller.up.into_inner() * controller.step_height),
+            skin_width,
+        );
+        let raised = stepped.translation.vector - pose.translation.vector;
+
+        let before = stepped.translation.vector;
+        self.cast(&mut stepped, translation, skin_width);
+        if (stepped.translation.vector - before).norm() <= skin_width {
+            return None;
+        }
+
+        match self.cast(&mut stepped, &(-raised), skin_width) {
+            Some((
+                _,
+                ShapeCastHit {
+                    normal: Some(normal),
+                    ..
+                },
+            )) if controller.is_walkable(&normal) => Some(stepped),
+            _ => None,
+        }
+    }
+
+    /// Checks for walkable ground right below the pose without moving it.
+    fn probe_ground(&self, controller: &mut CharacterController, pose: &Isometry3<Float>) {
+        let mut probe = *pose;
+        let translation = -controller.up.into_inner() * controller.skin_width * Float::from(2.0);
+
+        if let Some((_, hit)) = self.cast(&mut probe, &translation, Float::from(0.0)) {
+            if let Some(normal) = hit.normal {
+                if controller.is_walkable(&normal) {
+                    controller.grounded = true;
+                    controller.ground_normal = Some(normal);
+                    controller.ground = Some(hit.entity);
+                }
+            }
+        }
+    }
+
+    /// Moves the pose along the translation up to the first surface it hits,
+    /// keeping the skin width to it. Returns the remaining translation and the
+    /// hit, or `None` if the pose moved freely. Surfaces the shape is already
+    /// moving away from are ignored, so penetrations can be resolved, as well
+    /// as `OneWayPlatform`s hit from the side they can be passed through from,
+    /// before the `OneWayPlatformSystem` recorded the pair.
+    fn cast(
+        &self,
+        pose: &mut Isometry3<Float>,
+        translation: &Vector3<Float>,
+        skin_width: Float,
+    ) -> Option<(Vector3<Float>, ShapeCastHit)> {
+        let distance = translation.norm();
+        if distance <= Float::from(1.0e-6) {
+            return None;
+        }
+        let direction = translation / distance;
+
+        let hit = self
+            .query
+            .cast_shape_all(self.shape, pose, translation, &self.filter)
+            .into_iter()
+            .find(|hit| {
+                hit.normal.map_or(true, |normal| {
+                    normal.dot(&direction) < Float::from(0.0)
+                        && !self
+                            .platform_ups
+                            .get(&hit.entity)
+                            .map_or(false, |up| passes_through(&normal, up))
+                })
+            });
+
+        match hit {
+            Some(hit) => {
+                let mut travel = hit.toi * distance - skin_width;
+                if travel < Float::from(0.0) {
+                    travel = Float::from(0.0);
+                }
+                pose.translation.vector += direction * travel;
+                Some((direction * (distance - travel), hit))
+            }
+            None => {
+                pose.translation.vector += translation;
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use std::collections::{HashMap, HashSet};
+
+use amethyst::{
+    core::Float,
+    ecs::{
+        storage::ComponentEvent,
+        world::Index,
+        Entities,
+        Entity,
+        Join,
+        ReadStorage,
+        ReaderId,
+        Resources,
+        System,
+        SystemData,
+        WriteExpect,
+        WriteStorage,
+    },
+};
+
+use specs_physics::{
+    bodies::PhysicsBody,
+    colliders::PhysicsCollider,
+    nphysics::object::{BodyHandle, ColliderHandle},
+    Physics,
+};
+
+use super::iterate_component_events;
+
+/// The `PhysicsCleanupSystem` guarantees that bodies and colliders are removed
+/// from the physics world once their `PhysicsBody`/`PhysicsCollider` is removed
+/// or their `Entity` is deleted. In debug builds it additionally logs colliders
+/// that remain in the physics world without a matching `Entity`.
+#[derive(Default)]
+pub struct PhysicsCleanupSystem {
+    physics_bodies_reader_id: Option<ReaderId<ComponentEvent>>,
+    physics_colliders_reader_id: Option<ReaderId<ComponentEvent>>,
+    body_handles: HashMap<Index, (Entity, BodyHandle)>,
+    collider_handles: HashMap<Index, (Entity, ColliderHandle)>,
+    #[cfg(debug_assertions)]
+    reported_leaks: HashSet<ColliderHandle>,
+}
+
+impl<'s> System<'s> for PhysicsCleanupSystem {
+    type SystemData = (
+        Entities<'s>,
+        WriteExpect<'s, Physics<Float>>,
+        ReadStorage<'s, PhysicsBody<Float>>,
+        ReadStorage<'s, PhysicsCollider<Float>>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (entities, mut physics, physics_bodies, physics_colliders) = data;
+
+        let (_, _, removed_physics_bodies) = iterate_component_events(
+            &physics_bodies,
+            self.physics_bodies_reader_id.as_mut().unwrap(),
+        );
+        let (_, _, removed_physics_colliders) = iterate_component_events(
+            &physics_colliders,
+            self.physics_colliders_reader_id.as_mut().unwrap(),
+        );
+
+        // handles that are still owned by live components must never be removed,
+        // even if nphysics reused them after specs-physics freed the old ones
+        let live_bodies = (&physics_bodies)
+            .join()
+            .filter_map(|physics_body| physics_body.handle())
+            .collect::<HashSet<_>>();
+        let live_colliders = (&physics_colliders)
+            .join()
+            .filter_map(|physics_collider| physics_collider.handle())
+            .collect::<HashSet<_>>();
+
+        // handle removed PhysicsColliders and deleted Entities
+        let stale_colliders = self
+            .collider_handles
+            .iter()
+            .filter(|(id, (entity, _))| {
+                !entities.is_alive(*entity) || removed_physics_colliders.contains(**id)
+            })
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        for id in stale_colliders {
+            let (_, handle) = self.collider_handles.remove(&id).unwrap();
+            let world = physics.world_mut();
+            if !live_colliders.contains(&handle) && world.collider(handle).is_some() {
+                info!("Removing collider of Entity with id: {}", id);
+                world.remove_colliders(&[handle]);
+            }
+        }
+
+        // handle removed PhysicsBodies and deleted Entities; removing a body also
+        // removes all colliders attached to it
+        let stale_bodies = self
+            .body_handles
+            .iter()
+            .filter(|(id, (entity, _))| {
+                !entities.is_alive(*entity) || removed_physics_bodies.contains(**id)
+            })
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        for id in stale_bodies {
+            let (_, handle) = self.body_handles.remove(&id).unwrap();
+            let world = physics.world_mut();
+            if !live_bodies.contains(&handle) && world.body(handle).is_some() {
+                info!("Removing body of Entity with id: {}", id);
+                world.remove_bodies(&[handle]);
+            }
+        }
+
+        // track the handles of all live PhysicsBodies and PhysicsColliders
+        for (entity, physics_body) in (&entities, &physics_bodies).join() {
+            if let Some(handle) = physics_body.handle() {
+                self.body_handles.insert(entity.id(), (entity, handle));
+            }
+        }
+        for (entity, physics_collider) in (&entities, &physics_colliders).join() {
+            if let Some(handle) = physics_collider.handle() {
+                self.collider_handles.insert(entity.id(), (entity, handle));
+            }
+        }
+
+        #[cfg(debug_assertions)]
+        self.report_leaks(&entities, &physics, &physics_colliders);
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        info!("PhysicsCleanupSystem.setup");
+        Self::SystemData::setup(res);
+
+        // register reader id for the PhysicsBody storage
+        let mut physics_body_storage: WriteStorage<PhysicsBody<Float>> = SystemData::fetch(&res);
+        self.physics_bodies_reader_id = Some(physics_body_storage.register_reader());
+
+        // register reader id for the PhysicsCollider storage
+        let mut physics_collider_storage: WriteStorage<PhysicsCollider<Float>> =
+            SystemData::fetch(&res);
+        self.physics_colliders_reader_id = Some(physics_collider_storage.register_reader());
+    }
+}
+
+impl PhysicsCleanupSystem {
+    /// Logs every collider of the physics world that belongs to an `Entity`
+    /// which is either deleted or no longer has a `PhysicsCollider`. Every leak
+    /// is only reported once.
+    #[cfg(debug_assertions)]
+    fn report_leaks(
+        &mut self,
+        entities: &Entities,
+        physics: &Physics<Float>,
+        physics_colliders: &ReadStorage<PhysicsCollider<Float>>,
+    ) {
+        let mut leaks = HashSet::new();
+        for collider in physics.world().colliders() {
+            let entity = match crate::collider_entity(collider) {
+                Some(entity) => entity,
+                None => continue,
+            };
+
+            if !entities.is_alive(entity) || !physics_colliders.contains(entity) {
+                if !self.reported_leaks.contains(&collider.handle()) {
+                    warn!(
+                        "Leaked collider {:?} of Entity with id: {}",
+                        collider.handle(),
+                        entity.id()
+                    );
+                }
+                leaks.insert(collider.handle());
+            }
+        }
+
+        self.reported_leaks = leaks;
+    }
+}
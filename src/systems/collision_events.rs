@@ -0,0 +1,153 @@
+use amethyst::{
+    core::{
+        math::{Unit, Vector3},
+        Float,
+    },
+    ecs::{ReadExpect, Resources, System, SystemData, Write},
+    shrev::EventChannel,
+};
+
+use specs_physics::{
+    ncollide::{events::ContactEvent, query::Proximity, world::CollisionObjectHandle},
+    nphysics::world::World,
+    Physics,
+};
+
+use crate::{
+    collider_entity,
+    events::{CollisionEvent, CollisionKind, CollisionPhase},
+};
+
+/// The `CollisionEventSystem` translates the contact and proximity events of
+/// the last physics step into `CollisionEvent`s and publishes them on the
+/// `EventChannel<CollisionEvent>` resource.
+#[derive(Default)]
+pub struct CollisionEventSystem;
+
+impl<'s> System<'s> for CollisionEventSystem {
+    type SystemData = (
+        ReadExpect<'s, Physics<Float>>,
+        Write<'s, EventChannel<CollisionEvent>>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (physics, mut collision_events) = data;
+        let world = physics.world();
+
+        let mut events = Vec::new();
+
+        // handle contact events; normal and depth are only known while the
+        // contact is active
+        for contact_event in world.contact_events().iter() {
+            let event = match *contact_event {
+                ContactEvent::Started(handle1, handle2) => collision_event(
+                    world,
+                    handle1,
+                    handle2,
+                    CollisionKind::Contact,
+                    CollisionPhase::Started,
+                )
+                .map(|mut event| {
+                    if let Some((normal, depth)) = deepest_contact(world, handle1, handle2) {
+                        event.normal = Some(normal);
+                        event.depth = Some(depth);
+                    }
+                    event
+                }),
+                ContactEvent::Stopped(handle1, handle2) => collision_event(
+                    world,
+                    handle1,
+                    handle2,
+                    CollisionKind::Contact,
+                    CollisionPhase::Stopped,
+                ),
+            };
+
+            events.extend(event);
+        }
+
+        // handle proximity events; we only care about transitions into and out of
+        // the intersecting state
+        for proximity_event in world.proximity_events().iter() {
+            let phase = match (proximity_event.prev_status, proximity_event.new_status) {
+                (Proximity::Intersecting, Proximity::Intersecting) => continue,
+                (_, Proximity::Intersecting) => CollisionPhase::Started,
+                (Proximity::Intersecting, _) => CollisionPhase::Stopped,
+                _ => continue,
+            };
+
+            events.extend(collision_event(
+                world,
+                proximity_event.collider1,
+                proximity_event.collider2,
+                CollisionKind::Proximity,
+                phase,
+            ));
+        }
+
+        if !events.is_empty() {
+            debug!("Publishing {} CollisionEvents", events.len());
+            collision_events.iter_write(events);
+        }
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        info!("CollisionEventSystem.setup");
+        Self::SystemData::setup(res);
+
+        // initialise required resources
+        res.entry::<EventChannel<CollisionEvent>>()
+            .or_insert_with(EventChannel::default);
+    }
+}
+
+/// Creates a `CollisionEvent` without contact information for the `Entity`s of
+/// the given collider handles.
+fn collision_event(
+    world: &World<Float>,
+    handle1: CollisionObjectHandle,
+    handle2: CollisionObjectHandle,
+    kind: CollisionKind,
+    phase: CollisionPhase,
+) -> Option<CollisionEvent> {
+    let entity1 = world.collider(handle1).and_then(collider_entity);
+    let entity2 = world.collider(handle2).and_then(collider_entity);
+
+    match (entity1, entity2) {
+        (Some(entity1), Some(entity2)) => Some(CollisionEvent {
+            entity1,
+            entity2,
+            kind,
+            phase,
+            normal: None,
+            depth: None,
+        }),
+        _ => {
+            warn!(
+                "Failed to resolve Entities of colliders {:?} and {:?}",
+                handle1, handle2
+            );
+            None
+        }
+    }
+}
+
+/// Returns the normal (pointing from `handle1` towards `handle2`) and depth of
+/// the deepest contact point between the given colliders.
+pub(crate) fn deepest_contact(
+    world: &World<Float>,
+    handle1: CollisionObjectHandle,
+    handle2: CollisionObjectHandle,
+) -> Option<(Unit<Vector3<Float>>, Float)> {
+    let (collider1, _, _, manifold) = world
+        .collider_world()
+        .contact_pair(handle1, handle2, true)?;
+    let contact = &manifold.deepest_contact()?.contact;
+
+    // the contact pair may be stored in the opposite order
+    if collider1.handle() == handle1 {
+        Some((contact.normal, contact.depth))
+    } else {
+        Some((-contact.normal, contact.depth))
+    }
+}
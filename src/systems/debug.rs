@@ -1,93 +1,750 @@
+use std::{collections::HashMap, f32::consts::PI};
+
 use amethyst::{
-    core::{transform::Transform, Float},
-    ecs::{Join, ReadStorage, Resources, System, SystemData, Write},
-    renderer::{
-        debug_drawing::{DebugLines, DebugLinesParams},
-        palette::Srgba,
+    core::{
+        math::{Isometry3, Point3, Quaternion, Translation3, UnitQuaternion, Vector3},
+        Float,
+    },
+    ecs::{
+        Entities,
+        Entity,
+        Join,
+        Read,
+        ReadExpect,
+        ReadStorage,
+        Resources,
+        System,
+        SystemData,
+        Write,
     },
+    renderer::palette::Srgba,
 };
 
-use specs_physics::colliders::{PhysicsCollider, Shape};
+use specs_physics::{
+    bodies::{BodyStatus, PhysicsBody, Position},
+    colliders::PhysicsCollider,
+    ncollide::{
+        shape::{
+            Ball,
+            Capsule,
+            Compound,
+            ConvexHull,
+            Cuboid,
+            HeightField,
+            Plane,
+            Polyline,
+            Segment,
+            Shape,
+            TriMesh,
+            Triangle,
+        },
+        world::CollisionGroups,
+    },
+    Physics,
+};
 
-/// The `DebugSystem`s handles the drawing of `DebugLines` elements for
+use crate::{
+    debug::{PhysicsDebugGeometry, PhysicsDebugSettings, CIRCLE_SEGMENTS},
+    joints::{JointKind, PhysicsJoint},
+    PhysicsTransform,
+};
+
+/// Half size of the square drawn for the infinite `Plane` shape.
+const PLANE_HALF_EXTENT: f32 = 10.0;
+
+/// The `DebugSystem`s generates the `PhysicsDebugGeometry` for
 /// `PhysicsCollider`s. This visualises the `PhysicsCollider` and enables easier
-/// debugging of collisions.
+/// debugging of collisions. Outlines are drawn at the world-space position of
+/// the collider in the physics world, including its rotation and offset from
+/// the parent body, so what we draw is exactly what collides. Solid colliders
+/// are colored by the `BodyStatus` and activation state of their parent body.
+/// What is drawn is controlled by the `PhysicsDebugSettings` resource; how it
+/// is rendered is up to the consumers of the `PhysicsDebugGeometry`.
 #[derive(Default)]
-pub struct DebugSystem;
+pub struct DebugSystem {
+    settings: PhysicsDebugSettings,
+    previous_velocities: HashMap<Entity, Vector3<Float>>,
+}
 
 impl<'s> System<'s> for DebugSystem {
     type SystemData = (
-        ReadStorage<'s, Transform>,
+        Entities<'s>,
+        ReadExpect<'s, Physics<Float>>,
+        Read<'s, PhysicsDebugSettings>,
+        ReadStorage<'s, PhysicsTransform>,
+        ReadStorage<'s, PhysicsBody<Float>>,
         ReadStorage<'s, PhysicsCollider<Float>>,
-        Write<'s, DebugLines>,
+        ReadStorage<'s, PhysicsJoint>,
+        Write<'s, PhysicsDebugGeometry>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (transforms, physics_colliders, mut debug_lines) = data;
-
-        // iterate over PhysicsColliders and their Transforms and draw lines accordingly
-        for (transform, physics_collider) in (&transforms, &physics_colliders).join() {
-            // fetch the parent for its position
-            let (x, y, z) = (
-                transform.translation().x.as_f32(),
-                transform.translation().y.as_f32(),
-                transform.translation().z.as_f32(),
-            );
+        let (
+            entities,
+            physics,
+            settings,
+            physics_transforms,
+            physics_bodies,
+            physics_colliders,
+            physics_joints,
+            mut geometry,
+        ) = data;
 
-            // color based on type
+        geometry.clear();
+        if !settings.enabled {
+            return;
+        }
+
+        // iterate over PhysicsColliders and draw lines accordingly
+        for (entity, physics_collider) in (&entities, &physics_colliders).join() {
+            if physics_collider.sensor && !settings.sensors
+                || !physics_collider.sensor && !settings.colliders
+            {
+                continue;
+            }
+
+            // the actual shape lives in the physics world; skip PhysicsColliders that
+            // have not been added to the world yet
+            let collider = match physics_collider
+                .handle()
+                .and_then(|handle| physics.world().collider(handle))
+            {
+                Some(collider) => collider,
+                None => continue,
+            };
+
+            // the world-space position of the collider already contains the position
+            // of its parent body and its offset from said parent; the Transform scale
+            // is not applied to colliders and therefore ignored here as well
+            let isometry = isometry_f32(collider.position());
+
+            // color based on type; solid colliders are colored by the status and
+            // activation state of their parent body
             let color = if physics_collider.sensor {
-                Srgba::new(0.13, 0.65, 0.94, 1.0) // 1 or 1/255?!
+                settings.colors.sensor
             } else {
-                Srgba::new(0.81, 0.0, 0.5, 1.0) // 1 or 1/255?!
+                match physics.world().body(collider.body()) {
+                    Some(body) => match body.status() {
+                        BodyStatus::Static => settings.colors.static_body,
+                        BodyStatus::Kinematic => settings.colors.kinematic_body,
+                        BodyStatus::Disabled => settings.colors.disabled_body,
+                        BodyStatus::Dynamic if !body.is_active() => settings.colors.sleeping_body,
+                        BodyStatus::Dynamic => settings.colors.dynamic_body,
+                    },
+                    None => settings.colors.static_body,
+                }
             };
 
-            // depending on the Shape we draw the DebugLines differently; right now we only
-            // support Shape::Rectangle
-            match physics_collider.shape {
-                Shape::Rectangle(width, height, _) => {
-                    let width = width.as_f32();
-                    let height = height.as_f32();
-
-                    // draw top line
-                    debug_lines.draw_line(
-                        [x - width / 2.0, y + height / 2.0, z].into(),
-                        [x + width / 2.0, y + height / 2.0, z].into(),
-                        color,
-                    );
+            draw_shape(&mut geometry, collider.shape().as_ref(), &isometry, color);
 
-                    // draw right line
-                    debug_lines.draw_line(
-                        [x + width / 2.0, y + height / 2.0, z].into(),
-                        [x + width / 2.0, y - height / 2.0, z].into(),
-                        color,
-                    );
+            if settings.labels {
+                geometry.add_label(
+                    isometry * Point3::origin(),
+                    entity.id().to_string(),
+                    settings.colors.label,
+                );
+            }
+        }
 
-                    // draw bottom line
-                    debug_lines.draw_line(
-                        [x + width / 2.0, y - height / 2.0, z].into(),
-                        [x - width / 2.0, y - height / 2.0, z].into(),
-                        color,
-                    );
+        if settings.aabbs || settings.broad_phase {
+            draw_bounding_boxes(&mut geometry, &physics, &settings);
+        }
+
+        if settings.contacts {
+            draw_contacts(&mut geometry, &physics, &settings);
+        }
 
-                    // draw bottom line
-                    debug_lines.draw_line(
-                        [x - width / 2.0, y - height / 2.0, z].into(),
-                        [x - width / 2.0, y + height / 2.0, z].into(),
-                        color,
+        if settings.joints {
+            draw_joints(
+                &mut geometry,
+                &physics_transforms,
+                &physics_joints,
+                &settings,
+            );
+        }
+
+        if settings.velocities || settings.forces {
+            let timestep = physics.world().timestep();
+            let scale = settings.vector_scale;
+
+            let mut velocities = HashMap::new();
+            for (entity, physics_transform, physics_body) in
+                (&entities, &physics_transforms, &physics_bodies).join()
+            {
+                let origin = Point3::from(
+                    physics_transform
+                        .isometry()
+                        .translation
+                        .vector
+                        .map(|c| c.as_f32()),
+                );
+
+                if settings.velocities {
+                    // linear velocity as arrow from the body origin
+                    let linear = physics_body.velocity.map(|c| c.as_f32());
+                    draw_arrow(
+                        &mut geometry,
+                        &origin,
+                        &(origin + linear * scale),
+                        settings.colors.velocity,
                     );
+
+                    // angular velocity as axis plus an arc covering the rotation per second
+                    let angular = physics_body
+                        .handle()
+                        .and_then(|handle| physics.world().rigid_body(handle))
+                        .map(|rigid_body| rigid_body.velocity().angular.map(|c| c.as_f32()));
+                    if let Some(angular) = angular {
+                        draw_angular_velocity(&mut geometry, &origin, &angular, &settings);
+                    }
+                }
+
+                if settings.forces {
+                    // the net force is estimated from the change in velocity between
+                    // two physics steps
+                    if let Some(previous) = self.previous_velocities.get(&entity) {
+                        let acceleration = (physics_body.velocity - previous) / timestep;
+                        let force = (acceleration * physics_body.mass).map(|c| c.as_f32());
+                        draw_arrow(
+                            &mut geometry,
+                            &origin,
+                            &(origin + force * scale),
+                            settings.colors.force,
+                        );
+                    }
+                    velocities.insert(entity, physics_body.velocity);
                 }
-                _ => {}
             }
+            // only live Entities are kept, so reused ids start without a previous
+            // velocity
+            self.previous_velocities = velocities;
+        } else {
+            self.previous_velocities.clear();
         }
     }
 
     fn setup(&mut self, res: &mut Resources) {
         info!("DebugSystem.setup");
+
+        // initialise required resources; the PhysicsDebugSettings have to be
+        // inserted before the SystemData setup inserts their default
+        let settings = &self.settings;
+        res.entry::<PhysicsDebugSettings>()
+            .or_insert_with(|| settings.clone());
+        res.entry::<PhysicsDebugGeometry>()
+            .or_insert_with(PhysicsDebugGeometry::default);
+
         Self::SystemData::setup(res);
+    }
+}
+
+impl DebugSystem {
+    /// Creates a new `DebugSystem` that inserts the given
+    /// `PhysicsDebugSettings` if the resource does not exist yet.
+    pub fn new(settings: PhysicsDebugSettings) -> Self {
+        Self {
+            settings,
+            ..Default::default()
+        }
+    }
+}
+
+/// Draws the axis-aligned bounding boxes and/or broad-phase proxy bounds of all
+/// colliders in the physics world. Broad-phase proxies overlapping at least
+/// `broad_phase_hot_threshold` other proxies are highlighted as hot spots.
+fn draw_bounding_boxes(
+    geometry: &mut PhysicsDebugGeometry,
+    physics: &Physics<Float>,
+    settings: &PhysicsDebugSettings,
+) {
+    let identity = Isometry3::identity();
+    let collider_world = physics.world().collider_world();
+    let all_groups = CollisionGroups::new();
+
+    for collider in physics.world().colliders() {
+        if settings.aabbs {
+            let aabb = collider.shape().aabb(collider.position());
+            draw_box(
+                geometry,
+                &identity,
+                &aabb.mins().coords.map(|c| c.as_f32()),
+                &aabb.maxs().coords.map(|c| c.as_f32()),
+                settings.colors.aabb,
+            );
+        }
+
+        if settings.broad_phase {
+            let aabb = match collider_world
+                .as_collision_world()
+                .broad_phase_aabb(collider.handle())
+            {
+                Some(aabb) => aabb,
+                None => continue,
+            };
+
+            // the proxy itself is part of the interferences as well
+            let overlaps = collider_world
+                .interferences_with_aabb(aabb, &all_groups)
+                .count()
+                .saturating_sub(1);
+            let color = if overlaps >= settings.broad_phase_hot_threshold {
+                settings.colors.broad_phase_hot
+            } else {
+                settings.colors.broad_phase
+            };
+
+            draw_box(
+                geometry,
+                &identity,
+                &aabb.mins().coords.map(|c| c.as_f32()),
+                &aabb.maxs().coords.map(|c| c.as_f32()),
+                color,
+            );
+        }
+    }
+}
+
+/// Draws the contact points of all active contact manifolds in the physics
+/// world, together with their contact normals scaled by the penetration depth.
+fn draw_contacts(
+    geometry: &mut PhysicsDebugGeometry,
+    physics: &Physics<Float>,
+    settings: &PhysicsDebugSettings,
+) {
+    let color = settings.colors.contact;
+
+    for (_, _, _, manifold) in physics.world().collider_world().contact_pairs(true) {
+        for tracked_contact in manifold.contacts() {
+            let contact = &tracked_contact.contact;
+            let world1 = point_f32(&contact.world1);
+            let world2 = point_f32(&contact.world2);
+
+            // the contact points on both colliders
+            draw_point(geometry, &world1, settings.marker_size, color);
+            draw_point(geometry, &world2, settings.marker_size, color);
+
+            // the contact normal, pointing from the first towards the second collider
+            let normal = contact.normal.map(|c| c.as_f32()) * contact.depth.as_f32();
+            geometry.add_line(world1, world1 + normal, color);
+        }
+    }
+}
+
+/// Draws all `PhysicsJoint`s as line between their world-space anchors. The
+/// free axis of revolute and prismatic joints is drawn at the first anchor.
+fn draw_joints(
+    geometry: &mut PhysicsDebugGeometry,
+    physics_transforms: &ReadStorage<PhysicsTransform>,
+    physics_joints: &ReadStorage<PhysicsJoint>,
+    settings: &PhysicsDebugSettings,
+) {
+    let color = settings.colors.joint;
+
+    for (physics_transform, physics_joint) in (physics_transforms, physics_joints).join() {
+        let isometry = isometry_f32(physics_transform.isometry());
+        let anchor1 = isometry * point_f32(&physics_joint.anchor);
+
+        // without a target the anchor is already in world-space
+        let anchor2 = match physics_joint.target {
+            Some(target) => match physics_transforms.get(target) {
+                Some(target_transform) => {
+                    isometry_f32(target_transform.isometry())
+                        * point_f32(&physics_joint.target_anchor)
+                }
+                None => continue,
+            },
+            None => point_f32(&physics_joint.target_anchor),
+        };
 
-        // initialise required resources
-        res.entry::<DebugLines>().or_insert(DebugLines::new());
-        res.entry::<DebugLinesParams>()
-            .or_insert(DebugLinesParams { line_width: 1.0 });
+        geometry.add_line(anchor1, anchor2, color);
+        draw_point(geometry, &anchor1, settings.marker_size, color);
+        draw_point(geometry, &anchor2, settings.marker_size, color);
+
+        match physics_joint.kind {
+            JointKind::Revolute { axis, .. } | JointKind::Prismatic { axis, .. } => {
+                let axis =
+                    isometry.rotation * axis.map(|c| c.as_f32()) * settings.marker_size * 2.0;
+                geometry.add_line(anchor1 - axis, anchor1 + axis, color);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Draws an arrow between the given world-space points.
+fn draw_arrow(
+    geometry: &mut PhysicsDebugGeometry,
+    from: &Point3<f32>,
+    to: &Point3<f32>,
+    color: Srgba,
+) {
+    let direction = to - from;
+    let length = direction.norm();
+    if length <= std::f32::EPSILON {
+        return;
+    }
+
+    geometry.add_line(*from, *to, color);
+
+    // the arrow head spans a fifth of the arrow length
+    let (u, v) = orthonormal_basis(&direction);
+    let back = direction / length * (length * 0.2);
+    for side in &[u, -u, v, -v] {
+        geometry.add_line(*to, to - back + side * (length * 0.1), color);
+    }
+}
+
+/// Draws the axis of an angular velocity and an arc around it that covers the
+/// angle rotated within one second (clamped to a full turn).
+fn draw_angular_velocity(
+    geometry: &mut PhysicsDebugGeometry,
+    origin: &Point3<f32>,
+    angular: &Vector3<f32>,
+    settings: &PhysicsDebugSettings,
+) {
+    let speed = angular.norm();
+    if speed <= std::f32::EPSILON {
+        return;
+    }
+
+    let color = settings.colors.velocity;
+    geometry.add_line(*origin, origin + angular * settings.vector_scale, color);
+
+    let (u, v) = orthonormal_basis(angular);
+    draw_arc(
+        geometry,
+        &Isometry3::identity(),
+        origin,
+        &u,
+        &v,
+        settings.marker_size * 2.0,
+        (0.0, speed.min(2.0 * PI)),
+        color,
+    );
+}
+
+/// Draws a small cross at the given world-space point.
+fn draw_point(geometry: &mut PhysicsDebugGeometry, point: &Point3<f32>, size: f32, color: Srgba) {
+    let half_size = size / 2.0;
+    for axis in &[Vector3::x(), Vector3::y(), Vector3::z()] {
+        geometry.add_line(point - axis * half_size, point + axis * half_size, color);
+    }
+}
+
+/// Draws the outline of the given `Shape` at the given `Isometry3`. `Shape`s
+/// that are not known to the `DebugSystem` are drawn as their AABB.
+fn draw_shape(
+    geometry: &mut PhysicsDebugGeometry,
+    shape: &dyn Shape<Float>,
+    isometry: &Isometry3<f32>,
+    color: Srgba,
+) {
+    if let Some(ball) = shape.as_shape::<Ball<Float>>() {
+        let radius = ball.radius().as_f32();
+        draw_circle(geometry, isometry, Vector3::z(), radius, color);
+        draw_circle(geometry, isometry, Vector3::y(), radius, color);
+        draw_circle(geometry, isometry, Vector3::x(), radius, color);
+    } else if let Some(cuboid) = shape.as_shape::<Cuboid<Float>>() {
+        let half_extents = cuboid.half_extents().map(|c| c.as_f32());
+        draw_box(geometry, isometry, &-half_extents, &half_extents, color);
+    } else if let Some(capsule) = shape.as_shape::<Capsule<Float>>() {
+        draw_capsule(
+            geometry,
+            isometry,
+            capsule.half_height().as_f32(),
+            capsule.radius().as_f32(),
+            color,
+        );
+    } else if let Some(segment) = shape.as_shape::<Segment<Float>>() {
+        draw_local_line(geometry, isometry, segment.a(), segment.b(), color);
+    } else if let Some(triangle) = shape.as_shape::<Triangle<Float>>() {
+        draw_triangle(geometry, isometry, triangle, color);
+    } else if let Some(polyline) = shape.as_shape::<Polyline<Float>>() {
+        let points = polyline.points();
+        for edge in polyline.edges() {
+            draw_local_line(
+                geometry,
+                isometry,
+                &points[edge.indices.x],
+                &points[edge.indices.y],
+                color,
+            );
+        }
+    } else if let Some(tri_mesh) = shape.as_shape::<TriMesh<Float>>() {
+        let points = tri_mesh.points();
+        for edge in tri_mesh.edges() {
+            draw_local_line(
+                geometry,
+                isometry,
+                &points[edge.indices.x],
+                &points[edge.indices.y],
+                color,
+            );
+        }
+    } else if let Some(convex_hull) = shape.as_shape::<ConvexHull<Float>>() {
+        let points = convex_hull.points();
+        for edge in convex_hull.edges() {
+            draw_local_line(
+                geometry,
+                isometry,
+                &points[edge.vertices.x],
+                &points[edge.vertices.y],
+                color,
+            );
+        }
+    } else if let Some(height_field) = shape.as_shape::<HeightField<Float>>() {
+        for triangle in height_field.triangles() {
+            draw_triangle(geometry, isometry, &triangle, color);
+        }
+    } else if let Some(compound) = shape.as_shape::<Compound<Float>>() {
+        // every part of a Compound has its own offset relative to the Compound
+        for (part_isometry, part_shape) in compound.shapes() {
+            draw_shape(
+                geometry,
+                part_shape.as_ref(),
+                &(isometry * isometry_f32(part_isometry)),
+                color,
+            );
+        }
+    } else if let Some(plane) = shape.as_shape::<Plane<Float>>() {
+        draw_plane(
+            geometry,
+            isometry,
+            &plane.normal().map(|c| c.as_f32()),
+            color,
+        );
+    } else {
+        let aabb = shape.aabb(&Isometry3::identity());
+        draw_box(
+            geometry,
+            isometry,
+            &aabb.mins().coords.map(|c| c.as_f32()),
+            &aabb.maxs().coords.map(|c| c.as_f32()),
+            color,
+        );
+    }
+}
+
+/// Draws a circle around the local origin in the plane perpendicular to the
+/// given local `normal`.
+fn draw_circle(
+    geometry: &mut PhysicsDebugGeometry,
+    isometry: &Isometry3<f32>,
+    normal: Vector3<f32>,
+    radius: f32,
+    color: Srgba,
+) {
+    geometry.add_circle(
+        isometry * Point3::origin(),
+        isometry * normal,
+        radius,
+        color,
+    );
+}
+
+/// Draws an arc with the given `radius` around `center`, spanned by the local
+/// unit vectors `u` and `v`, from `angles.0` to `angles.1` (in radians).
+#[allow(clippy::too_many_arguments)]
+fn draw_arc(
+    geometry: &mut PhysicsDebugGeometry,
+    isometry: &Isometry3<f32>,
+    center: &Point3<f32>,
+    u: &Vector3<f32>,
+    v: &Vector3<f32>,
+    radius: f32,
+    angles: (f32, f32),
+    color: Srgba,
+) {
+    let (start, end) = angles;
+    let segments =
+        ((CIRCLE_SEGMENTS as f32 * (end - start).abs() / (2.0 * PI)).ceil() as usize).max(1);
+    let step = (end - start) / segments as f32;
+
+    let point_at = |angle: f32| center + (u * angle.cos() + v * angle.sin()) * radius;
+    for segment in 0..segments {
+        let from = point_at(start + step * segment as f32);
+        let to = point_at(start + step * (segment + 1) as f32);
+        geometry.add_line(isometry * from, isometry * to, color);
+    }
+}
+
+/// Draws the wireframe of the local box spanned by `mins` and `maxs`.
+fn draw_box(
+    geometry: &mut PhysicsDebugGeometry,
+    isometry: &Isometry3<f32>,
+    mins: &Vector3<f32>,
+    maxs: &Vector3<f32>,
+    color: Srgba,
+) {
+    let corner = |x: bool, y: bool, z: bool| {
+        isometry
+            * Point3::new(
+                if x { maxs.x } else { mins.x },
+                if y { maxs.y } else { mins.y },
+                if z { maxs.z } else { mins.z },
+            )
+    };
+
+    for &(a, b) in &[
+        // edges along the x axis
+        ((false, false, false), (true, false, false)),
+        ((false, true, false), (true, true, false)),
+        ((false, false, true), (true, false, true)),
+        ((false, true, true), (true, true, true)),
+        // edges along the y axis
+        ((false, false, false), (false, true, false)),
+        ((true, false, false), (true, true, false)),
+        ((false, false, true), (false, true, true)),
+        ((true, false, true), (true, true, true)),
+        // edges along the z axis
+        ((false, false, false), (false, false, true)),
+        ((true, false, false), (true, false, true)),
+        ((false, true, false), (false, true, true)),
+        ((true, true, false), (true, true, true)),
+    ] {
+        geometry.add_line(corner(a.0, a.1, a.2), corner(b.0, b.1, b.2), color);
+    }
+}
+
+/// Draws a `Capsule` whose segment lies on the local y axis, as seen from the
+/// front (xy plane) and the side (zy plane), plus the rings at both ends.
+fn draw_capsule(
+    geometry: &mut PhysicsDebugGeometry,
+    isometry: &Isometry3<f32>,
+    half_height: f32,
+    radius: f32,
+    color: Srgba,
+) {
+    let top = Point3::new(0.0, half_height, 0.0);
+    let bottom = Point3::new(0.0, -half_height, 0.0);
+
+    for side in &[Vector3::x(), Vector3::z()] {
+        // the straight sides of the capsule
+        for &offset in &[side * radius, -side * radius] {
+            geometry.add_line(
+                isometry * (top + offset),
+                isometry * (bottom + offset),
+                color,
+            );
+        }
+
+        // the half circles at both ends
+        let up = Vector3::y();
+        draw_arc(
+            geometry,
+            isometry,
+            &top,
+            side,
+            &up,
+            radius,
+            (0.0, PI),
+            color,
+        );
+        draw_arc(
+            geometry,
+            isometry,
+            &bottom,
+            side,
+            &-up,
+            radius,
+            (0.0, PI),
+            color,
+        );
+    }
+
+    for center in &[top, bottom] {
+        draw_arc(
+            geometry,
+            isometry,
+            center,
+            &Vector3::x(),
+            &Vector3::z(),
+            radius,
+            (0.0, 2.0 * PI),
+            color,
+        );
     }
 }
+
+/// Draws a square around the local origin in the `Plane`, plus its normal.
+fn draw_plane(
+    geometry: &mut PhysicsDebugGeometry,
+    isometry: &Isometry3<f32>,
+    normal: &Vector3<f32>,
+    color: Srgba,
+) {
+    let (u, v) = orthonormal_basis(normal);
+    let (u, v) = (u * PLANE_HALF_EXTENT, v * PLANE_HALF_EXTENT);
+    let corners = [
+        Point3::origin() + u + v,
+        Point3::origin() + u - v,
+        Point3::origin() - u - v,
+        Point3::origin() - u + v,
+    ];
+
+    for i in 0..corners.len() {
+        geometry.add_line(
+            isometry * corners[i],
+            isometry * corners[(i + 1) % corners.len()],
+            color,
+        );
+    }
+
+    geometry.add_line(
+        isometry * Point3::origin(),
+        isometry * (Point3::origin() + normal),
+        color,
+    );
+}
+
+/// Draws the three edges of a `Triangle`.
+fn draw_triangle(
+    geometry: &mut PhysicsDebugGeometry,
+    isometry: &Isometry3<f32>,
+    triangle: &Triangle<Float>,
+    color: Srgba,
+) {
+    draw_local_line(geometry, isometry, triangle.a(), triangle.b(), color);
+    draw_local_line(geometry, isometry, triangle.b(), triangle.c(), color);
+    draw_local_line(geometry, isometry, triangle.c(), triangle.a(), color);
+}
+
+/// Draws a line between two local `Point3<Float>`s.
+fn draw_local_line(
+    geometry: &mut PhysicsDebugGeometry,
+    isometry: &Isometry3<f32>,
+    a: &Point3<Float>,
+    b: &Point3<Float>,
+    color: Srgba,
+) {
+    geometry.add_line(isometry * point_f32(a), isometry * point_f32(b), color);
+}
+
+/// Returns two unit vectors that are perpendicular to the given `normal` and to
+/// each other.
+fn orthonormal_basis(normal: &Vector3<f32>) -> (Vector3<f32>, Vector3<f32>) {
+    let normal = normal.normalize();
+    let reference = if normal.x.abs() < 0.9 {
+        Vector3::x()
+    } else {
+        Vector3::y()
+    };
+    let u = normal.cross(&reference).normalize();
+    let v = normal.cross(&u);
+    (u, v)
+}
+
+/// Converts a `Point3<Float>` into a `Point3<f32>`.
+fn point_f32(point: &Point3<Float>) -> Point3<f32> {
+    Point3::from(point.coords.map(|c| c.as_f32()))
+}
+
+/// Converts an `Isometry3<Float>` into an `Isometry3<f32>`.
+fn isometry_f32(isometry: &Isometry3<Float>) -> Isometry3<f32> {
+    Isometry3::from_parts(
+        Translation3::from(isometry.translation.vector.map(|c| c.as_f32())),
+        UnitQuaternion::new_unchecked(Quaternion::from(
+            isometry.rotation.quaternion().coords.map(|c| c.as_f32()),
+        )),
+    )
+}
@@ -0,0 +1,51 @@
+use amethyst::{
+    ecs::{Read, ReadExpect, Resources, System, SystemData, Write},
+    renderer::debug_drawing::{DebugLines, DebugLinesParams},
+};
+
+use crate::debug::{PhysicsDebugGeometry, PhysicsDebugSettings, CIRCLE_SEGMENTS};
+
+/// The `DebugLinesSystem` renders the `PhysicsDebugGeometry` via amethyst's
+/// `DebugLines`. Circles are approximated with line segments; labels are not
+/// supported by `DebugLines` and therefore skipped.
+#[derive(Default)]
+pub struct DebugLinesSystem;
+
+impl<'s> System<'s> for DebugLinesSystem {
+    type SystemData = (
+        ReadExpect<'s, PhysicsDebugGeometry>,
+        Read<'s, PhysicsDebugSettings>,
+        Write<'s, DebugLines>,
+        Write<'s, DebugLinesParams>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (geometry, settings, mut debug_lines, mut debug_lines_params) = data;
+
+        if geometry.is_empty() {
+            return;
+        }
+
+        debug_lines_params.line_width = settings.line_width;
+
+        for line in &geometry.lines {
+            debug_lines.draw_line(line.start, line.end, line.color);
+        }
+
+        for circle in &geometry.circles {
+            for (start, end) in circle.segments(CIRCLE_SEGMENTS) {
+                debug_lines.draw_line(start, end, circle.color);
+            }
+        }
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        info!("DebugLinesSystem.setup");
+        Self::SystemData::setup(res);
+
+        // initialise required resources
+        res.entry::<DebugLines>().or_insert(DebugLines::new());
+        res.entry::<DebugLinesParams>()
+            .or_insert(DebugLinesParams { line_width: 1.0 });
+    }
+}
@@ -0,0 +1,103 @@
+use std::{cmp::Ordering, collections::HashMap};
+
+use amethyst::{
+    core::{
+        math::{Unit, Vector3},
+        Float,
+    },
+    ecs::{
+        Entities,
+        Entity,
+        Join,
+        ReadExpect,
+        ReadStorage,
+        Resources,
+        System,
+        SystemData,
+        WriteStorage,
+    },
+};
+
+use specs_physics::{bodies::PhysicsBody, Physics};
+
+use crate::{character::CharacterController, collider_entity, platformer::GroundSensor};
+
+/// The `GroundSensorSystem` updates every `GroundSensor` after the physics
+/// step. `Entity`s with a `CharacterController` take over its ground state, all
+/// others use the contacts of their colliders.
+#[derive(Default)]
+pub struct GroundSensorSystem;
+
+impl<'s> System<'s> for GroundSensorSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadExpect<'s, Physics<Float>>,
+        ReadStorage<'s, PhysicsBody<Float>>,
+        ReadStorage<'s, CharacterController>,
+        WriteStorage<'s, GroundSensor>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (entities, physics, physics_bodies, character_controllers, mut ground_sensors) = data;
+
+        let world = physics.world();
+        let timestep = world.timestep();
+
+        // collect the surface normals of all touching Entities, as seen by each
+        // Entity; contact normals point from the first towards the second collider
+        let mut contacts: HashMap<Entity, Vec<(Entity, Unit<Vector3<Float>>)>> = HashMap::new();
+        for (collider1, collider2, _, manifold) in world.collider_world().contact_pairs(true) {
+            let contact = match manifold.deepest_contact() {
+                Some(tracked_contact) => &tracked_contact.contact,
+                None => continue,
+            };
+
+            if let (Some(entity1), Some(entity2)) =
+                (collider_entity(collider1), collider_entity(collider2))
+            {
+                contacts
+                    .entry(entity1)
+                    .or_default()
+                    .push((entity2, -contact.normal));
+                contacts
+                    .entry(entity2)
+                    .or_default()
+                    .push((entity1, contact.normal));
+            }
+        }
+
+        for (entity, ground_sensor) in (&entities, &mut ground_sensors).join() {
+            let ground = match character_controllers.get(entity) {
+                Some(controller) => match (controller.ground(), controller.ground_normal()) {
+                    (Some(ground), Some(normal)) => Some((ground, normal)),
+                    _ => None,
+                },
+                None => contacts.get(&entity).and_then(|contacts| {
+                    // the most upright walkable surface is the ground
+                    contacts
+                        .iter()
+                        .filter(|(_, normal)| ground_sensor.is_walkable(normal))
+                        .max_by(|(_, a), (_, b)| {
+                            let up = &ground_sensor.up;
+                            a.dot(up).partial_cmp(&b.dot(up)).unwrap_or(Ordering::Equal)
+                        })
+                        .cloned()
+                }),
+            };
+
+            let ground = ground.map(|(ground, normal)| {
+                let velocity = physics_bodies
+                    .get(ground)
+                    .map_or_else(Vector3::zeros, |physics_body| physics_body.velocity);
+                (ground, normal, velocity)
+            });
+
+            ground_sensor.update(ground, timestep);
+        }
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        info!("GroundSensorSystem.setup");
+        Self::SystemData::setup(res);
+    }
+}
@@ -0,0 +1,293 @@
+use std::collections::HashMap;
+
+use amethyst::{
+    core::{
+        math::{Isometry3, Translation3, UnitQuaternion, Vector3},
+        Float,
+    },
+    ecs::{
+        storage::ComponentEvent,
+        world::Index,
+        Entities,
+        Entity,
+        Join,
+        ReadStorage,
+        ReaderId,
+        Resources,
+        System,
+        SystemData,
+        WriteExpect,
+        WriteStorage,
+    },
+};
+
+use specs_physics::{
+    bodies::PhysicsBody,
+    nphysics::{
+        algebra::Velocity3,
+        joint::{
+            BallConstraint,
+            ConstraintHandle,
+            FixedConstraint,
+            MouseConstraint,
+            PrismaticConstraint,
+            RevoluteConstraint,
+        },
+        object::{BodyHandle, BodyPartHandle},
+        world::World,
+    },
+    Physics,
+};
+
+use super::iterate_component_events;
+use crate::joints::{JointKind, JointMotor, PhysicsJoint};
+
+/// The `JointSystem` adds the joints of inserted `PhysicsJoint`s to the physics
+/// world once both connected bodies exist, and removes them again when the
+/// `PhysicsJoint` or one of the bodies is removed. Modified `PhysicsJoint`s
+/// are recreated. `JointMotor`s are applied every step.
+#[derive(Default)]
+pub struct JointSystem {
+    physics_joints_reader_id: Option<ReaderId<ComponentEvent>>,
+    joints: HashMap<Index, JointHandles>,
+}
+
+/// The handles of a joint in the physics world and of the bodies it connects.
+struct JointHandles {
+    entity: Entity,
+    target: Option<Entity>,
+    body1: BodyHandle,
+    body2: Option<BodyHandle>,
+    constraint: ConstraintHandle,
+}
+
+impl<'s> System<'s> for JointSystem {
+    type SystemData = (
+        Entities<'s>,
+        WriteExpect<'s, Physics<Float>>,
+        ReadStorage<'s, PhysicsBody<Float>>,
+        ReadStorage<'s, PhysicsJoint>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (entities, mut physics, physics_bodies, physics_joints) = data;
+
+        let (_, modified_physics_joints, removed_physics_joints) = iterate_component_events(
+            &physics_joints,
+            self.physics_joints_reader_id.as_mut().unwrap(),
+        );
+
+        let body_handle = |entity: Entity| {
+            physics_bodies
+                .get(entity)
+                .and_then(|physics_body| physics_body.handle())
+        };
+
+        // remove joints that were modified or removed, or whose bodies are gone
+        let stale = self
+            .joints
+            .iter()
+            .filter(|(id, joint)| {
+                modified_physics_joints.contains(**id)
+                    || removed_physics_joints.contains(**id)
+                    || !entities.is_alive(joint.entity)
+                    || !physics_joints.contains(joint.entity)
+                    || body_handle(joint.entity) != Some(joint.body1)
+                    || joint
+                        .target
+                        .map_or(false, |target| !entities.is_alive(target))
+                    || joint.target.and_then(body_handle) != joint.body2
+            })
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        let world = physics.world_mut();
+        for id in stale {
+            let joint = self.joints.remove(&id).unwrap();
+            // the joint might already be gone together with one of its bodies
+            if world.constraint(joint.constraint).is_some() {
+                info!("Removing joint of Entity with id: {}", id);
+                world.remove_constraint(joint.constraint);
+            }
+        }
+
+        // add joints whose bodies exist in the physics world
+        for (entity, physics_joint) in (&entities, &physics_joints).join() {
+            if self.joints.contains_key(&entity.id()) {
+                continue;
+            }
+
+            let body1 =
+                match body_handle(entity).filter(|handle| world.rigid_body(*handle).is_some()) {
+                    Some(body1) => body1,
+                    None => continue,
+                };
+            let body2 = match physics_joint.target {
+                Some(target) => {
+                    match body_handle(target).filter(|handle| world.rigid_body(*handle).is_some()) {
+                        Some(body2) => Some(body2),
+                        None => continue,
+                    }
+                }
+                None => None,
+            };
+
+            info!("Adding joint of Entity with id: {}", entity.id());
+            let constraint = add_constraint(world, physics_joint, body1, body2);
+            self.joints.insert(
+                entity.id(),
+                JointHandles {
+                    entity,
+                    target: physics_joint.target,
+                    body1,
+                    body2,
+                    constraint,
+                },
+            );
+        }
+
+        // apply motors
+        let timestep = world.timestep();
+        for (entity, physics_joint) in (&entities, &physics_joints).join() {
+            let joint = match self.joints.get(&entity.id()) {
+                Some(joint) => joint,
+                None => continue,
+            };
+
+            match physics_joint.kind {
+                JointKind::Revolute {
+                    axis,
+                    motor: Some(motor),
+                    ..
+                } => apply_motor(world, joint, &axis, &motor, true, timestep),
+                JointKind::Prismatic {
+                    axis,
+                    motor: Some(motor),
+                    ..
+                } => apply_motor(world, joint, &axis, &motor, false, timestep),
+                _ => {}
+            }
+        }
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        info!("JointSystem.setup");
+        Self::SystemData::setup(res);
+
+        // register reader id for the PhysicsJoint storage
+        let mut physics_joint_storage: WriteStorage<PhysicsJoint> = SystemData::fetch(&res);
+        self.physics_joints_reader_id = Some(physics_joint_storage.register_reader());
+    }
+}
+
+/// Adds the joint described by the given `PhysicsJoint` between the given
+/// bodies to the physics world. The anchors of the second body are chosen so
+/// that the current relative orientation of both bodies is kept.
+fn add_constraint(
+    world: &mut World<Float>,
+    physics_joint: &PhysicsJoint,
+    body1: BodyHandle,
+    body2: Option<BodyHandle>,
+) -> ConstraintHandle {
+    let (part1, position1) = {
+        let rigid_body = world.rigid_body(body1).unwrap();
+        (rigid_body.part_handle(), *rigid_body.position())
+    };
+    let (part2, position2) = match body2.and_then(|body2| world.rigid_body(body2)) {
+        Some(rigid_body) => (rigid_body.part_handle(), *rigid_body.position()),
+        None => (BodyPartHandle::ground(), Isometry3::identity()),
+    };
+
+    // rotates directions from the frame of the first into the frame of the
+    // second body
+    let relative_rotation = position2.rotation.inverse() * position1.rotation;
+    let anchor1 = physics_joint.anchor;
+    let anchor2 = physics_joint.target_anchor;
+
+    match physics_joint.kind {
+        JointKind::Fixed => world.add_constraint(FixedConstraint::new(
+            part1,
+            part2,
+            Isometry3::from_parts(
+                Translation3::from(anchor1.coords),
+                UnitQuaternion::identity(),
+            ),
+            Isometry3::from_parts(Translation3::from(anchor2.coords), relative_rotation),
+        )),
+        JointKind::Revolute { axis, limits, .. } => {
+            let mut constraint = RevoluteConstraint::new(
+                part1,
+                part2,
+                anchor1,
+                axis,
+                anchor2,
+                relative_rotation * axis,
+            );
+            if let Some(limits) = limits {
+                constraint.enable_min_angle(limits.min);
+                constraint.enable_max_angle(limits.max);
+            }
+            world.add_constraint(constraint)
+        }
+        JointKind::Prismatic { axis, limits, .. } => {
+            let mut constraint = PrismaticConstraint::new(part1, part2, anchor1, axis, anchor2);
+            if let Some(limits) = limits {
+                constraint.enable_min_offset(limits.min);
+                constraint.enable_max_offset(limits.max);
+            }
+            world.add_constraint(constraint)
+        }
+        JointKind::Ball => {
+            world.add_constraint(BallConstraint::new(part1, part2, anchor1, anchor2))
+        }
+        JointKind::Mouse { max_force } => world.add_constraint(MouseConstraint::new(
+            part2, part1, anchor2, anchor1, max_force,
+        )),
+    }
+}
+
+/// Changes the velocity of the first body of the given joint along the given
+/// local axis towards the target velocity of the `JointMotor`, relative to the
+/// second body.
+fn apply_motor(
+    world: &mut World<Float>,
+    joint: &JointHandles,
+    axis: &Vector3<Float>,
+    motor: &JointMotor,
+    angular: bool,
+    timestep: Float,
+) {
+    let target_velocity = joint
+        .body2
+        .and_then(|body2| world.rigid_body(body2))
+        .map_or_else(Velocity3::zero, |rigid_body| *rigid_body.velocity());
+
+    let rigid_body = match world.rigid_body_mut(joint.body1) {
+        Some(rigid_body) => rigid_body,
+        None => return,
+    };
+
+    let axis = rigid_body.position().rotation * axis;
+    let velocity = *rigid_body.velocity();
+    let current = if angular {
+        (velocity.angular - target_velocity.angular).dot(&axis)
+    } else {
+        (velocity.linear - target_velocity.linear).dot(&axis)
+    };
+
+    // the motor can only change the velocity by a limited amount per step
+    let max_change = motor.max_acceleration * timestep;
+    let mut change = motor.target_velocity - current;
+    if change > max_change {
+        change = max_change;
+    } else if change < -max_change {
+        change = -max_change;
+    }
+
+    let velocity = if angular {
+        Velocity3::new(velocity.linear, velocity.angular + axis * change)
+    } else {
+        Velocity3::new(velocity.linear + axis * change, velocity.angular)
+    };
+    rigid_body.set_velocity(velocity);
+    rigid_body.activate();
+}
@@ -1,5 +1,6 @@
 use amethyst::{
-    core::{bundle::SystemBundle, Float},
+    config::Config,
+    core::{bundle::SystemBundle, math::Vector3, Float},
     ecs::{
         storage::{ComponentEvent, MaskedStorage},
         BitSet,
@@ -11,48 +12,146 @@ use amethyst::{
     },
     error::Error,
 };
-use std::ops::Deref;
+use std::{ops::Deref, path::Path, sync::Arc};
+
+pub use self::transform_interpolation::TransformInterpolationSystem;
 
 use self::{
+    character_controller::CharacterControllerSystem,
+    cleanup::PhysicsCleanupSystem,
+    collision_events::CollisionEventSystem,
     debug::DebugSystem,
+    debug_lines::DebugLinesSystem,
+    ground_sensor::GroundSensorSystem,
+    joints::JointSystem,
+    one_way_platforms::{OneWayPlatformSystem, PairFilter},
+    platform_paths::PlatformPathSystem,
+    platform_riders::PlatformRiderSystem,
     sync_transforms_from_physics::SyncTransformsFromPhysicsSystem,
     sync_transforms_to_physics::SyncTransformsToPhysicsSystem,
+    triggers::TriggerSystem,
 };
 
-use specs_physics::register_physics_systems;
+use specs_physics::{
+    ncollide::broad_phase::BroadPhasePairFilter,
+    nphysics::object::{Collider, ColliderHandle},
+    register_physics_systems,
+};
 
-use crate::PhysicsTransform;
+use crate::{config::PhysicsConfig, debug::PhysicsDebugSettings, PhysicsTransform};
 
+mod character_controller;
+mod cleanup;
+mod collision_events;
 mod debug;
+mod debug_lines;
+mod ground_sensor;
+mod joints;
+mod one_way_platforms;
+mod platform_paths;
+mod platform_riders;
 mod sync_transforms_from_physics;
 mod sync_transforms_to_physics;
+mod transform_interpolation;
+mod triggers;
 
 #[derive(Default)]
 pub struct PhysicsBundle {
-    debug_lines: bool,
+    broad_phase_pair_filter: Option<PairFilter>,
+    config: PhysicsConfig,
+    debug_settings: PhysicsDebugSettings,
+    headless_debug: bool,
+    interpolation: bool,
+    trigger_stay_events: bool,
 }
 
 impl<'a, 'b> SystemBundle<'a, 'b> for PhysicsBundle {
     fn build(self, dispatcher: &mut DispatcherBuilder) -> Result<(), Error> {
         dispatcher.add(
-            SyncTransformsToPhysicsSystem::default(),
+            SyncTransformsToPhysicsSystem::new(self.config.clone()),
             "sync_transforms_to_physics_system",
             &[],
         );
 
+        dispatcher.add(
+            OneWayPlatformSystem::new(self.broad_phase_pair_filter),
+            "one_way_platform_system",
+            &["sync_transforms_to_physics_system"],
+        );
+
+        dispatcher.add(
+            PlatformPathSystem::default(),
+            "platform_path_system",
+            &["sync_transforms_to_physics_system"],
+        );
+
+        dispatcher.add(
+            PlatformRiderSystem::default(),
+            "platform_rider_system",
+            &["platform_path_system"],
+        );
+
+        dispatcher.add(
+            CharacterControllerSystem::default(),
+            "character_controller_system",
+            &["one_way_platform_system", "platform_path_system"],
+        );
+
+        // joints have to be removed before the bodies they connect and added
+        // before the physics step, which is why this runs ahead of the
+        // specs-physics systems registered below
+        dispatcher.add(
+            JointSystem::default(),
+            "joint_system",
+            &["sync_transforms_to_physics_system"],
+        );
+
         register_physics_systems::<Float, PhysicsTransform>(dispatcher);
 
         dispatcher.add(
-            SyncTransformsFromPhysicsSystem::default(),
+            PhysicsCleanupSystem::default(),
+            "physics_cleanup_system",
+            &[
+                "sync_bodies_to_physics_system",
+                "sync_colliders_to_physics_system",
+            ],
+        );
+
+        dispatcher.add(
+            CollisionEventSystem::default(),
+            "collision_event_system",
+            &["physics_stepper_system"],
+        );
+
+        dispatcher.add(
+            TriggerSystem::new(self.trigger_stay_events),
+            "trigger_system",
+            &["collision_event_system"],
+        );
+
+        dispatcher.add(
+            GroundSensorSystem::default(),
+            "ground_sensor_system",
+            &["sync_positions_from_physics_system"],
+        );
+
+        dispatcher.add(
+            SyncTransformsFromPhysicsSystem::new(self.config, self.interpolation),
             "sync_transforms_from_physics_system",
             &["sync_positions_from_physics_system"],
         );
 
-        if self.debug_lines {
+        dispatcher.add(
+            DebugSystem::new(self.debug_settings),
+            "debug_system",
+            &["sync_transforms_from_physics_system"],
+        );
+
+        if !self.headless_debug {
             dispatcher.add(
-                DebugSystem::default(),
-                "debug_system",
-                &["sync_transforms_from_physics_system"],
+                DebugLinesSystem::default(),
+                "debug_lines_system",
+                &["debug_system"],
             );
         }
 
@@ -61,10 +160,101 @@ impl<'a, 'b> SystemBundle<'a, 'b> for PhysicsBundle {
 }
 
 impl PhysicsBundle {
-    /// Enables the `DebugSystem` which draws `DebugLines` around
-    /// `PhysicsCollider` shapes.
+    /// Creates a new `PhysicsBundle` from the `PhysicsConfig` RON file at the
+    /// given path.
+    pub fn from_config_path(path: impl AsRef<Path>) -> Self {
+        Self::default().with_config(PhysicsConfig::load(path))
+    }
+
+    /// Replaces the `PhysicsConfig` used to create the `Physics` resource.
+    pub fn with_config(mut self, config: PhysicsConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Sets the gravity vector of the physics world.
+    pub fn with_gravity(mut self, gravity: Vector3<Float>) -> Self {
+        self.config.gravity = (gravity.x.as_f32(), gravity.y.as_f32(), gravity.z.as_f32());
+        self
+    }
+
+    /// Sets the duration (in seconds) of a single physics step. This should
+    /// match the rate the `PhysicsBundle` is dispatched at.
+    pub fn with_timestep(mut self, timestep: Float) -> Self {
+        self.config.timestep = timestep.as_f32();
+        self
+    }
+
+    /// Sets the maximum number of velocity constraint solver iterations per
+    /// step.
+    pub fn with_max_velocity_iterations(mut self, iterations: usize) -> Self {
+        self.config.max_velocity_iterations = iterations;
+        self
+    }
+
+    /// Sets the maximum number of position constraint solver iterations per
+    /// step.
+    pub fn with_max_position_iterations(mut self, iterations: usize) -> Self {
+        self.config.max_position_iterations = iterations;
+        self
+    }
+
+    /// Sets the broad phase pair filter of the physics world. The
+    /// `PhysicsBundle` installs its own filter for `OneWayPlatform`s, which
+    /// replaces any filter set on the physics world directly, so custom
+    /// filters have to be passed here to be chained.
+    pub fn with_broad_phase_pair_filter<F>(mut self, filter: F) -> Self
+    where
+        F: BroadPhasePairFilter<Float, Collider<Float>, ColliderHandle> + Send + Sync + 'static,
+    {
+        self.broad_phase_pair_filter = Some(Arc::new(filter));
+        self
+    }
+
+    /// Sets the maximum number of continuous collision detection substeps per
+    /// step. Continuous collision detection still has to be enabled on the
+    /// colliders of fast bodies.
+    pub fn with_max_ccd_substeps(mut self, substeps: usize) -> Self {
+        self.config.max_ccd_substeps = substeps;
+        self
+    }
+
+    /// Initially enables the `DebugSystem` which draws `DebugLines` around
+    /// `PhysicsCollider` shapes. The debug rendering can be toggled at runtime
+    /// through the `PhysicsDebugSettings` resource.
     pub fn with_debug_lines(mut self) -> Self {
-        self.debug_lines = true;
+        self.debug_settings.enabled = true;
+        self
+    }
+
+    /// Only generates the `PhysicsDebugGeometry` resource without rendering it
+    /// via `DebugLines`, e.g. to export it to SVG files in headless tests.
+    pub fn with_headless_debug(mut self) -> Self {
+        self.headless_debug = true;
+        self
+    }
+
+    /// Replaces the initial `PhysicsDebugSettings`.
+    pub fn with_debug_settings(mut self, debug_settings: PhysicsDebugSettings) -> Self {
+        self.debug_settings = debug_settings;
+        self
+    }
+
+    /// Enables `Transform` interpolation between fixed physics steps. Instead
+    /// of snapping `Transform`s to the latest physics step, the previous
+    /// and current poses are kept and the `TransformInterpolationSystem`
+    /// has to be added to the variable rate update to write the
+    /// interpolated `Transform`s. `Entity`s with a `NoInterpolation`
+    /// `Component` keep snapping.
+    pub fn with_interpolation(mut self) -> Self {
+        self.interpolation = true;
+        self
+    }
+
+    /// Enables the publishing of `TriggerEventKind::Stay` events for every
+    /// `Entity` that remains inside a sensor collider.
+    pub fn with_trigger_stay_events(mut self) -> Self {
+        self.trigger_stay_events = true;
         self
     }
 }
@@ -98,3 +288,42 @@ where
 
     (inserted, modified, removed)
 }
+
+#[cfg(test)]
+mod tests {
+    use amethyst::{
+        core::bundle::SystemBundle,
+        ecs::{DispatcherBuilder, Resources},
+        renderer::debug_drawing::DebugLines,
+    };
+
+    use super::PhysicsBundle;
+    use crate::debug::PhysicsDebugGeometry;
+
+    fn setup(bundle: PhysicsBundle) -> Resources {
+        let mut builder = DispatcherBuilder::new();
+        bundle.build(&mut builder).unwrap();
+
+        let mut res = Resources::new();
+        builder.build().setup(&mut res);
+        res
+    }
+
+    #[test]
+    fn debug_lines() {
+        let res = setup(PhysicsBundle::default().with_debug_lines());
+        assert!(res.has_value::<PhysicsDebugGeometry>());
+        assert!(res.has_value::<DebugLines>());
+    }
+
+    #[test]
+    fn headless_debug() {
+        let res = setup(
+            PhysicsBundle::default()
+                .with_debug_lines()
+                .with_headless_debug(),
+        );
+        assert!(res.has_value::<PhysicsDebugGeometry>());
+        assert!(!res.has_value::<DebugLines>());
+    }
+}
@@ -0,0 +1,341 @@
+use std::{collections::HashSet, sync::Arc};
+
+use amethyst::{
+    core::{math::Vector3, Float},
+    ecs::{
+        Entities,
+        Entity,
+        Join,
+        ReadStorage,
+        Resources,
+        System,
+        SystemData,
+        Write,
+        WriteExpect,
+        WriteStorage,
+    },
+};
+
+use specs_physics::{
+    colliders::PhysicsCollider,
+    ncollide::{
+        bounding_volume::BoundingVolume,
+        broad_phase::BroadPhasePairFilter,
+        query::{self, Contact},
+        world::CollisionGroups,
+    },
+    nphysics::object::{Collider, ColliderHandle},
+    Physics,
+};
+
+use crate::{
+    collider_entity,
+    platformer::{DropThrough, OneWayPlatform, OneWayPlatformPairs},
+};
+
+/// The `OneWayPlatformSystem` determines which colliders currently pass
+/// through `OneWayPlatform`s by the normal of their contact with the platform,
+/// and excludes these pairs from collision detection. nphysics has no hook to
+/// modify single contacts, so the pairs are rejected by a broad phase pair
+/// filter instead. It also removes `DropThrough` requests of `Entity`s that
+/// left all `OneWayPlatform`s.
+///
+/// The system takes over the broad phase pair filter of the physics world; a
+/// custom filter has to be passed to the `PhysicsBundle` instead, which is
+/// consulted for all other pairs.
+#[derive(Default)]
+pub struct OneWayPlatformSystem {
+    pair_filter: Option<PairFilter>,
+    installed: bool,
+    pairs: HashSet<(Entity, Entity)>,
+}
+
+/// A broad phase pair filter that can be shared with the physics world.
+pub type PairFilter =
+    Arc<dyn BroadPhasePairFilter<Float, Collider<Float>, ColliderHandle> + Send + Sync>;
+
+impl<'s> System<'s> for OneWayPlatformSystem {
+    type SystemData = (
+        Entities<'s>,
+        WriteExpect<'s, Physics<Float>>,
+        ReadStorage<'s, OneWayPlatform>,
+        ReadStorage<'s, PhysicsCollider<Float>>,
+        WriteStorage<'s, DropThrough>,
+        Write<'s, OneWayPlatformPairs>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            mut physics,
+            one_way_platforms,
+            physics_colliders,
+            mut drop_throughs,
+            mut one_way_platform_pairs,
+        ) = data;
+
+        let mut pairs = HashSet::new();
+        let mut dropping = HashSet::new();
+
+        let world = physics.world();
+        for (platform_entity, platform, physics_collider) in
+            (&entities, &one_way_platforms, &physics_colliders).join()
+        {
+            let collider = match physics_collider
+                .handle()
+                .and_then(|handle| world.collider(handle))
+            {
+                Some(collider) => collider,
+                None => continue,
+            };
+
+            let up = platform_up(collider, platform);
+            let platform_aabb = collider.shape().aabb(collider.position());
+
+            for other in world.collider_world().interferences_with_aabb(
+                &platform_aabb.loosened(platform.tolerance),
+                &CollisionGroups::new(),
+            ) {
+                let entity = match collider_entity(other) {
+                    Some(entity) if entity != platform_entity => entity,
+                    _ => continue,
+                };
+
+                let contact = match query::contact(
+                    collider.position(),
+                    collider.shape().as_ref(),
+                    other.position(),
+                    other.shape().as_ref(),
+                    platform.tolerance,
+                ) {
+                    Some(contact) => contact,
+                    None => continue,
+                };
+
+                let drop_through = drop_throughs.contains(entity);
+                if drop_through {
+                    dropping.insert(entity);
+                }
+
+                let passing = self.pairs.contains(&(platform_entity, entity));
+                if ignores_contact(&contact, &up, platform.tolerance, passing, drop_through) {
+                    pairs.insert((platform_entity, entity));
+                }
+            }
+        }
+
+        // drop through requests are finished once no OneWayPlatform is near anymore
+        let finished = (&entities, &drop_throughs)
+            .join()
+            .map(|(entity, _)| entity)
+            .filter(|entity| !dropping.contains(entity))
+            .collect::<Vec<_>>();
+        for entity in finished {
+            debug!("Finished DropThrough of Entity with id: {}", entity.id());
+            drop_throughs.remove(entity);
+        }
+
+        // the broad phase only re-evaluates existing pairs if the filter is replaced
+        if !self.installed || pairs != self.pairs {
+            debug!("Updating OneWayPlatform pairs: {:?}", pairs);
+            physics
+                .world_mut()
+                .set_broad_phase_pair_filter(Some(OneWayPlatformFilter {
+                    pairs: pairs.clone(),
+                    inner: self.pair_filter.clone(),
+                }));
+            self.installed = true;
+            self.pairs = pairs.clone();
+        }
+
+        one_way_platform_pairs.pairs = pairs;
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        info!("OneWayPlatformSystem.setup");
+        Self::SystemData::setup(res);
+    }
+}
+
+impl OneWayPlatformSystem {
+    /// Creates a new `OneWayPlatformSystem` that chains the given broad phase
+    /// pair filter.
+    pub fn new(pair_filter: Option<PairFilter>) -> Self {
+        Self {
+            pair_filter,
+            ..Default::default()
+        }
+    }
+}
+
+/// The `OneWayPlatformFilter` rejects all collider pairs of `OneWayPlatform`s
+/// and the `Entity`s that currently pass through them. All other pairs are
+/// passed on to the chained filter, if any.
+struct OneWayPlatformFilter {
+    pairs: HashSet<(Entity, Entity)>,
+    inner: Option<PairFilter>,
+}
+
+impl BroadPhasePairFilter<Float, Collider<Float>, ColliderHandle> for OneWayPlatformFilter {
+    fn is_pair_valid(
+        &self,
+        collider1: &Collider<Float>,
+        collider2: &Collider<Float>,
+        handle1: ColliderHandle,
+        handle2: ColliderHandle,
+    ) -> bool {
+        let passing = match (collider_entity(collider1), collider_entity(collider2)) {
+            (Some(entity1), Some(entity2)) => {
+                self.pairs.contains(&(entity1, entity2)) || self.pairs.contains(&(entity2, entity1))
+            }
+            _ => false,
+        };
+
+        !passing
+            && self.inner.as_ref().map_or(true, |inner| {
+                inner.is_pair_valid(collider1, collider2, handle1, handle2)
+            })
+    }
+}
+
+/// Checks whether the contact between a `OneWayPlatform` and another collider
+/// is ignored. Contacts whose normal points against the pass-through direction
+/// `up`, e.g. from below or the side, let the collider pass through the
+/// platform until it separated from it again. Contacts deeper than the
+/// tolerance are never on top of the platform.
+fn ignores_contact(
+    contact: &Contact<Float>,
+    up: &Vector3<Float>,
+    tolerance: Float,
+    passing: bool,
+    drop_through: bool,
+) -> bool {
+    if drop_through || (passing && contact.depth > Float::from(0.0)) {
+        return true;
+    }
+
+    passes_through(&contact.normal, up) || contact.depth > tolerance
+}
+
+/// Returns the world-space pass-through direction of the given
+/// `OneWayPlatform`, which rotates with its collider.
+pub(crate) fn platform_up(collider: &Collider<Float>, platform: &OneWayPlatform) -> Vector3<Float> {
+    collider.position().rotation * platform.direction.into_inner()
+}
+
+/// Checks whether a collider touching a `OneWayPlatform` passes through it.
+/// The `normal` points from the platform towards the collider, `up` is the
+/// world-space pass-through direction of the platform.
+pub(crate) fn passes_through(normal: &Vector3<Float>, up: &Vector3<Float>) -> bool {
+    normal.dot(up) <= Float::from(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use amethyst::core::{
+        math::{Isometry3, Vector3},
+        Float,
+    };
+    use specs_physics::ncollide::{
+        query::{self, Contact},
+        shape::Cuboid,
+    };
+
+    use super::{ignores_contact, passes_through};
+
+    const TOLERANCE: f32 = 0.1;
+
+    /// Returns the contact between a 4x1 platform at the origin and a 1x1 box
+    /// at the given position.
+    fn contact(x: f32, y: f32) -> Contact<Float> {
+        let platform = Cuboid::new(Vector3::new(2.0, 0.5, 0.5).map(Float::from));
+        let other = Cuboid::new(Vector3::new(0.5, 0.5, 0.5).map(Float::from));
+
+        query::contact(
+            &Isometry3::identity(),
+            &platform,
+            &Isometry3::translation(x.into(), y.into(), 0.0.into()),
+            &other,
+            TOLERANCE.into(),
+        )
+        .unwrap()
+    }
+
+    fn ignores(contact: &Contact<Float>, passing: bool, drop_through: bool) -> bool {
+        ignores_contact(
+            contact,
+            &Vector3::y(),
+            TOLERANCE.into(),
+            passing,
+            drop_through,
+        )
+    }
+
+    #[test]
+    fn blocks_from_above() {
+        assert!(!ignores(&contact(0.0, 1.0), false, false));
+        assert!(!ignores(&contact(0.0, 0.95), false, false));
+    }
+
+    #[test]
+    fn passes_from_below() {
+        assert!(ignores(&contact(0.0, -1.0), false, false));
+        assert!(ignores(&contact(0.0, -0.5), false, false));
+    }
+
+    #[test]
+    fn passes_from_the_side() {
+        assert!(ignores(&contact(-2.45, 0.0), false, false));
+        assert!(ignores(&contact(2.45, 0.2), false, false));
+
+        // and keeps passing while sinking in, even if the shallowest
+        // penetration is on top
+        assert!(!ignores(&contact(-1.0, 0.92), false, false));
+        assert!(ignores(&contact(-1.0, 0.92), true, false));
+    }
+
+    #[test]
+    fn rotated_platform() {
+        // a platform rotated by 90 degrees lets colliders pass through its sides
+        let up = Vector3::x();
+        assert!(!ignores_contact(
+            &contact(2.5, 0.0),
+            &up,
+            TOLERANCE.into(),
+            false,
+            false
+        ));
+        assert!(ignores_contact(
+            &contact(-2.5, 0.0),
+            &up,
+            TOLERANCE.into(),
+            false,
+            false
+        ));
+        assert!(ignores_contact(
+            &contact(0.0, 1.0),
+            &up,
+            TOLERANCE.into(),
+            false,
+            false
+        ));
+    }
+
+    #[test]
+    fn hit_normals() {
+        // the CharacterControllerSystem checks the normals of its sweep hits
+        let up = Vector3::y();
+        assert!(passes_through(&-up, &up));
+        assert!(passes_through(&Vector3::x(), &up));
+        assert!(!passes_through(&up, &up));
+    }
+
+    #[test]
+    fn drops_through() {
+        assert!(ignores(&contact(0.0, 1.0), false, true));
+        assert!(ignores(&contact(0.0, 0.5), true, true));
+
+        // once separated, the platform blocks again
+        assert!(!ignores(&contact(0.0, 1.05), true, false));
+    }
+}
@@ -0,0 +1,53 @@
+use amethyst::{
+    core::Float,
+    ecs::{Join, ReadExpect, ReadStorage, Resources, System, SystemData, WriteStorage},
+};
+
+use specs_physics::{
+    bodies::{PhysicsBody, Position},
+    Physics,
+};
+
+use crate::{platformer::PlatformPath, PhysicsTransform};
+
+/// The `PlatformPathSystem` advances every `PlatformPath` by one physics step
+/// and sets the velocity of its (kinematic) `PhysicsBody` so that it reaches
+/// the new position on the path with the next step.
+#[derive(Default)]
+pub struct PlatformPathSystem;
+
+impl<'s> System<'s> for PlatformPathSystem {
+    type SystemData = (
+        ReadExpect<'s, Physics<Float>>,
+        ReadStorage<'s, PhysicsTransform>,
+        WriteStorage<'s, PlatformPath>,
+        WriteStorage<'s, PhysicsBody<Float>>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (physics, physics_transforms, mut platform_paths, mut physics_bodies) = data;
+
+        let timestep = physics.world().timestep();
+
+        for (physics_transform, platform_path, physics_body) in (
+            &physics_transforms,
+            &mut platform_paths,
+            &mut physics_bodies,
+        )
+            .join()
+        {
+            let distance = platform_path.speed * timestep;
+            if let Some(target) = platform_path.advance(distance) {
+                // aiming for the target instead of integrating the path velocity
+                // keeps the PhysicsBody from drifting off the path
+                let position = physics_transform.isometry().translation.vector;
+                physics_body.velocity = (target - position) / timestep;
+            }
+        }
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        info!("PlatformPathSystem.setup");
+        Self::SystemData::setup(res);
+    }
+}
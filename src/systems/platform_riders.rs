@@ -0,0 +1,55 @@
+use amethyst::{
+    core::{math::Vector3, Float},
+    ecs::{Entities, Join, ReadStorage, Resources, System, SystemData, WriteStorage},
+};
+
+use specs_physics::bodies::PhysicsBody;
+
+use crate::platformer::{GroundSensor, PlatformRider};
+
+/// The `PlatformRiderSystem` adds the velocity of the ground every
+/// `PlatformRider` stands on to its `PhysicsBody`. Only the change of the
+/// ground velocity since the previous step is applied, as the `PhysicsBody`
+/// keeps the previously inherited velocity.
+#[derive(Default)]
+pub struct PlatformRiderSystem;
+
+impl<'s> System<'s> for PlatformRiderSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, GroundSensor>,
+        WriteStorage<'s, PlatformRider>,
+        WriteStorage<'s, PhysicsBody<Float>>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (entities, ground_sensors, mut platform_riders, mut physics_bodies) = data;
+
+        for (entity, ground_sensor, platform_rider) in
+            (&entities, &ground_sensors, &mut platform_riders).join()
+        {
+            // the ground velocity of the GroundSensor is one step old, the
+            // PhysicsBody of the ground already contains the upcoming velocity
+            let ground_velocity = match ground_sensor.ground() {
+                Some(ground) => physics_bodies
+                    .get(ground)
+                    .map_or_else(Vector3::zeros, |physics_body| physics_body.velocity),
+                None => {
+                    // leaving the ground keeps the inherited velocity as momentum
+                    platform_rider.inherited_velocity = Vector3::zeros();
+                    continue;
+                }
+            };
+
+            if let Some(physics_body) = physics_bodies.get_mut(entity) {
+                physics_body.velocity += ground_velocity - platform_rider.inherited_velocity;
+                platform_rider.inherited_velocity = ground_velocity;
+            }
+        }
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        info!("PlatformRiderSystem.setup");
+        Self::SystemData::setup(res);
+    }
+}
@@ -2,6 +2,7 @@ use amethyst::{
     core::{transform::Transform, Float},
     ecs::{
         storage::ComponentEvent,
+        Entities,
         Join,
         ReadStorage,
         ReaderId,
@@ -13,38 +14,89 @@ use amethyst::{
 };
 
 use super::iterate_component_events;
-use crate::PhysicsTransform;
+use crate::{
+    config::PhysicsConfig,
+    InterpolatedIsometry,
+    NoInterpolation,
+    PhysicsTransform,
+    SyncedIsometry,
+};
 
 use specs_physics::{bodies::Position, Physics};
 
 #[derive(Default)]
 pub struct SyncTransformsFromPhysicsSystem {
+    config: PhysicsConfig,
+    interpolation: bool,
     physics_transforms_reader_id: Option<ReaderId<ComponentEvent>>,
 }
 
 impl<'s> System<'s> for SyncTransformsFromPhysicsSystem {
     type SystemData = (
+        Entities<'s>,
         ReadStorage<'s, PhysicsTransform>,
+        ReadStorage<'s, NoInterpolation>,
         WriteStorage<'s, Transform>,
+        WriteStorage<'s, SyncedIsometry>,
+        WriteStorage<'s, InterpolatedIsometry>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (physics_transforms, mut transforms) = data;
+        let (
+            entities,
+            physics_transforms,
+            no_interpolations,
+            mut transforms,
+            mut synced_isometries,
+            mut interpolated_isometries,
+        ) = data;
 
         let (_, modified_physics_transforms, _) = iterate_component_events(
             &physics_transforms,
             self.physics_transforms_reader_id.as_mut().unwrap(),
         );
 
-        for (physics_transforms, transform, _) in (
+        // a new physics step has been performed; the current poses become the
+        // previous ones, regardless of whether the Entity moved or not
+        if self.interpolation {
+            for interpolated_isometry in (&mut interpolated_isometries).join() {
+                interpolated_isometry.previous = interpolated_isometry.current;
+            }
+        }
+
+        for (entity, physics_transforms, transform, _) in (
+            &entities,
             &physics_transforms,
             &mut transforms,
             &modified_physics_transforms,
         )
             .join()
         {
-            let (x, y, z) = physics_transforms.position();
-            transform.set_translation_xyz(x, y, z);
+            // interpolated Entities get their Transform written by the
+            // TransformInterpolationSystem
+            if self.interpolation && !no_interpolations.contains(entity) {
+                if let Some(interpolated_isometry) = interpolated_isometries.get_mut(entity) {
+                    interpolated_isometry.current = *physics_transforms.isometry();
+                } else if let Err(err) = interpolated_isometries.insert(
+                    entity,
+                    InterpolatedIsometry::from(*physics_transforms.isometry()),
+                ) {
+                    warn!("Failed to insert InterpolatedIsometry: {}", err);
+                }
+
+                continue;
+            }
+
+            // copy translation and rotation; the scale is owned by the Transform
+            transform.set_isometry(*physics_transforms.isometry());
+
+            // remember what we wrote, so the SyncTransformsToPhysicsSystem does not
+            // echo this modification back into the physics world
+            if let Err(err) =
+                synced_isometries.insert(entity, SyncedIsometry(*physics_transforms.isometry()))
+            {
+                warn!("Failed to insert SyncedIsometry: {}", err);
+            }
         }
     }
 
@@ -53,11 +105,26 @@ impl<'s> System<'s> for SyncTransformsFromPhysicsSystem {
         Self::SystemData::setup(res);
 
         // initialise required resources
+        let config = &self.config;
         res.entry::<Physics<Float>>()
-            .or_insert_with(Physics::default);
+            .or_insert_with(|| config.create_physics());
 
         // register reader id for the PhysicsTransform storage
         let mut physics_transform_storage: WriteStorage<PhysicsTransform> = SystemData::fetch(&res);
         self.physics_transforms_reader_id = Some(physics_transform_storage.register_reader());
     }
 }
+
+impl SyncTransformsFromPhysicsSystem {
+    /// Creates a new `SyncTransformsFromPhysicsSystem` that applies the given
+    /// `PhysicsConfig` if it creates the `Physics` resource. If
+    /// `interpolation` is enabled, `Transform`s are no longer written directly
+    /// but left to the `TransformInterpolationSystem`.
+    pub fn new(config: PhysicsConfig, interpolation: bool) -> Self {
+        Self {
+            config,
+            interpolation,
+            ..Default::default()
+        }
+    }
+}
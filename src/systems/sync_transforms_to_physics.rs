@@ -12,68 +12,139 @@ use amethyst::{
         WriteStorage,
     },
 };
-use specs_physics::{bodies::Position, Physics};
+use specs_physics::{
+    bodies::{PhysicsBody, Position},
+    colliders::PhysicsCollider,
+    Physics,
+};
 
-use crate::PhysicsTransform;
+use crate::{config::PhysicsConfig, InterpolatedIsometry, PhysicsTransform, SyncedIsometry};
 
 use super::iterate_component_events;
 
 #[derive(Default)]
 pub struct SyncTransformsToPhysicsSystem {
+    config: PhysicsConfig,
     transforms_reader_id: Option<ReaderId<ComponentEvent>>,
-    // TODO: inserted PhysicsBody/PhysicsCollider
+    physics_bodies_reader_id: Option<ReaderId<ComponentEvent>>,
+    physics_colliders_reader_id: Option<ReaderId<ComponentEvent>>,
 }
 
 impl<'s> System<'s> for SyncTransformsToPhysicsSystem {
     type SystemData = (
         Entities<'s>,
         ReadStorage<'s, Transform>,
+        ReadStorage<'s, PhysicsBody<Float>>,
+        ReadStorage<'s, PhysicsCollider<Float>>,
+        WriteStorage<'s, SyncedIsometry>,
         WriteStorage<'s, PhysicsTransform>,
+        WriteStorage<'s, InterpolatedIsometry>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (entities, transforms, mut physics_transforms) = data;
+        let (
+            entities,
+            transforms,
+            physics_bodies,
+            physics_colliders,
+            mut synced_isometries,
+            mut physics_transforms,
+            mut interpolated_isometries,
+        ) = data;
 
         let (inserted_transforms, modified_transforms, removed_transforms) =
             iterate_component_events(&transforms, self.transforms_reader_id.as_mut().unwrap());
+        let (inserted_physics_bodies, ..) = iterate_component_events(
+            &physics_bodies,
+            self.physics_bodies_reader_id.as_mut().unwrap(),
+        );
+        let (inserted_physics_colliders, ..) = iterate_component_events(
+            &physics_colliders,
+            self.physics_colliders_reader_id.as_mut().unwrap(),
+        );
 
         for (entity, transform, id) in (
             &entities,
             &transforms,
-            &inserted_transforms | &modified_transforms | &removed_transforms,
+            &inserted_transforms | &modified_transforms,
         )
             .join()
         {
             // handle inserted events
             if inserted_transforms.contains(id) {
                 debug!("Inserted Transform with id: {}", id);
-                if let Err(err) = physics_transforms
-                    .insert(entity, PhysicsTransform::from(*transform.translation()))
+                if let Err(err) =
+                    physics_transforms.insert(entity, PhysicsTransform::from(*transform.isometry()))
                 {
                     warn!("Failed to insert PhysicsTransform: {}", err);
                 }
             }
 
-            //// handle modified events
-            //if modified_transforms.contains(id) {
-            //    debug!("Modified Transform with id: {}", id);
-            //    if let Some(physics_transform) = physics_transforms.get_mut(entity) {
-            //        physics_transform.set_position(
-            //            transform.translation().x,
-            //            transform.translation().y,
-            //            transform.translation().z,
-            //        );
-            //    }
-            //}
-
-            // handle removed events
-            if removed_transforms.contains(id) {
-                debug!("Removed Transform with id: {}", id);
-                if let Some(_) = physics_transforms.remove(entity) {
-                    info!("Removed PhysicsTransform with id: {}", id);
+            // handle modified events; modifications that match the last isometry
+            // written by the SyncTransformsFromPhysicsSystem originate from the
+            // physics world itself, everything else is treated as a teleport
+            if modified_transforms.contains(id) {
+                debug!("Modified Transform with id: {}", id);
+                let synced = synced_isometries
+                    .get(entity)
+                    .map_or(false, |synced| synced.0 == *transform.isometry());
+
+                if !synced {
+                    if let Some(physics_transform) = physics_transforms.get_mut(entity) {
+                        debug!("Teleporting PhysicsTransform with id: {}", id);
+                        *physics_transform.isometry_mut() = *transform.isometry();
+                    }
+
+                    // teleports must not be interpolated from the old pose; once
+                    // applied, the TransformInterpolationSystem takes over again
+                    if let Some(interpolated_isometry) = interpolated_isometries.get_mut(entity) {
+                        *interpolated_isometry = InterpolatedIsometry::from(*transform.isometry());
+                        if let Err(err) =
+                            synced_isometries.insert(entity, SyncedIsometry(*transform.isometry()))
+                        {
+                            warn!("Failed to insert SyncedIsometry: {}", err);
+                        }
+                    }
                 }
             }
         }
+
+        // handle inserted PhysicsBodies and PhysicsColliders; the PhysicsTransform
+        // has to reflect the current Transform before specs-physics adds them to
+        // the physics world, regardless of the order the components were added in
+        for (entity, transform, id) in (
+            &entities,
+            &transforms,
+            &inserted_physics_bodies | &inserted_physics_colliders,
+        )
+            .join()
+        {
+            // Transforms inserted this frame already received their PhysicsTransform
+            if inserted_transforms.contains(id) {
+                continue;
+            }
+
+            if let Some(physics_transform) = physics_transforms.get_mut(entity) {
+                debug!("Refreshing PhysicsTransform with id: {}", id);
+                *physics_transform.isometry_mut() = *transform.isometry();
+            } else {
+                debug!("Inserting PhysicsTransform with id: {}", id);
+                if let Err(err) =
+                    physics_transforms.insert(entity, PhysicsTransform::from(*transform.isometry()))
+                {
+                    warn!("Failed to insert PhysicsTransform: {}", err);
+                }
+            }
+        }
+
+        // handle removed events; removed Transforms can't be part of the join above
+        // and a Transform may have been re-inserted in the meantime
+        for (entity, id, _) in (&entities, &removed_transforms, !&transforms).join() {
+            debug!("Removed Transform with id: {}", id);
+            if physics_transforms.remove(entity).is_some() {
+                info!("Removed PhysicsTransform with id: {}", id);
+            }
+        }
     }
 
     fn setup(&mut self, res: &mut Resources) {
@@ -81,11 +152,32 @@ impl<'s> System<'s> for SyncTransformsToPhysicsSystem {
         Self::SystemData::setup(res);
 
         // initialise required resources
+        let config = &self.config;
         res.entry::<Physics<Float>>()
-            .or_insert_with(Physics::default);
+            .or_insert_with(|| config.create_physics());
 
         // register reader id for the Transform storage
         let mut transform_storage: WriteStorage<Transform> = SystemData::fetch(&res);
         self.transforms_reader_id = Some(transform_storage.register_reader());
+
+        // register reader id for the PhysicsBody storage
+        let mut physics_body_storage: WriteStorage<PhysicsBody<Float>> = SystemData::fetch(&res);
+        self.physics_bodies_reader_id = Some(physics_body_storage.register_reader());
+
+        // register reader id for the PhysicsCollider storage
+        let mut physics_collider_storage: WriteStorage<PhysicsCollider<Float>> =
+            SystemData::fetch(&res);
+        self.physics_colliders_reader_id = Some(physics_collider_storage.register_reader());
+    }
+}
+
+impl SyncTransformsToPhysicsSystem {
+    /// Creates a new `SyncTransformsToPhysicsSystem` that applies the given
+    /// `PhysicsConfig` if it creates the `Physics` resource.
+    pub fn new(config: PhysicsConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
     }
 }
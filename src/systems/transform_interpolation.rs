@@ -0,0 +1,181 @@
+use amethyst::{
+    core::{timing::Time, transform::Transform, Float},
+    ecs::{Entities, Join, Read, ReadStorage, Resources, System, SystemData, WriteStorage},
+};
+
+use crate::{InterpolatedIsometry, NoInterpolation, SyncedIsometry};
+
+/// The `TransformInterpolationSystem` writes `Transform`s that are interpolated
+/// between the two most recent physics steps, using the interpolation alpha of
+/// the `Time` resource. It has to run in the variable rate update (e.g. as part
+/// of the `GameData`) while the `PhysicsBundle` is dispatched in the fixed
+/// update with interpolation enabled.
+///
+/// `Transform`s modified by user systems since they were last synchronized
+/// are pending teleports and are left untouched until the next physics step
+/// applied them. The system should run after the user systems that modify
+/// `Transform`s and before the `TransformSystem`, so the rendered global
+/// matrices reflect the interpolated poses of the current frame.
+#[derive(Default)]
+pub struct TransformInterpolationSystem;
+
+impl<'s> System<'s> for TransformInterpolationSystem {
+    type SystemData = (
+        Entities<'s>,
+        Read<'s, Time>,
+        ReadStorage<'s, InterpolatedIsometry>,
+        ReadStorage<'s, NoInterpolation>,
+        WriteStorage<'s, Transform>,
+        WriteStorage<'s, SyncedIsometry>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            time,
+            interpolated_isometries,
+            no_interpolations,
+            mut transforms,
+            mut synced_isometries,
+        ) = data;
+
+        let alpha = Float::from(time.interpolation_alpha());
+
+        for (entity, interpolated_isometry, transform, _) in (
+            &entities,
+            &interpolated_isometries,
+            &mut transforms,
+            !&no_interpolations,
+        )
+            .join()
+        {
+            // pending teleports are picked up by the next physics step
+            let teleported = synced_isometries
+                .get(entity)
+                .map_or(false, |synced| synced.0 != *transform.isometry());
+            if teleported {
+                continue;
+            }
+
+            let isometry = interpolated_isometry
+                .previous
+                .lerp_slerp(&interpolated_isometry.current, alpha);
+            transform.set_isometry(isometry);
+
+            // the interpolated isometry is not a teleport and must not be written
+            // back into the physics world
+            if let Err(err) = synced_isometries.insert(entity, SyncedIsometry(isometry)) {
+                warn!("Failed to insert SyncedIsometry: {}", err);
+            }
+        }
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        info!("TransformInterpolationSystem.setup");
+        Self::SystemData::setup(res);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use amethyst::{
+        core::{math::Isometry3, transform::Transform, Float},
+        ecs::{Builder, RunNow, World},
+    };
+    use specs_physics::bodies::Position;
+
+    use super::TransformInterpolationSystem;
+    use crate::{
+        config::PhysicsConfig,
+        systems::sync_transforms_to_physics::SyncTransformsToPhysicsSystem,
+        InterpolatedIsometry,
+        PhysicsTransform,
+        SyncedIsometry,
+    };
+
+    fn isometry(x: f32) -> Isometry3<Float> {
+        Isometry3::translation(x.into(), 0.0.into(), 0.0.into())
+    }
+
+    fn transform(isometry: Isometry3<Float>) -> Transform {
+        let mut transform = Transform::default();
+        transform.set_isometry(isometry);
+        transform
+    }
+
+    #[test]
+    fn teleport_under_interpolation() {
+        let mut world = World::new();
+        let mut sync_system = SyncTransformsToPhysicsSystem::new(PhysicsConfig::default());
+        let mut interpolation_system = TransformInterpolationSystem::default();
+        RunNow::setup(&mut sync_system, &mut world.res);
+        RunNow::setup(&mut interpolation_system, &mut world.res);
+
+        // an Entity that moves from 0 to 1 in the latest physics step
+        let entity = world
+            .create_entity()
+            .with(transform(isometry(0.0)))
+            .with(SyncedIsometry(isometry(0.0)))
+            .with(InterpolatedIsometry {
+                previous: isometry(1.0),
+                current: isometry(1.0),
+            })
+            .build();
+        sync_system.run_now(&world.res);
+
+        interpolation_system.run_now(&world.res);
+        assert_eq!(
+            *world
+                .read_storage::<Transform>()
+                .get(entity)
+                .unwrap()
+                .isometry(),
+            isometry(1.0)
+        );
+
+        // teleports made by user systems survive the interpolation
+        world
+            .write_storage::<Transform>()
+            .get_mut(entity)
+            .unwrap()
+            .set_isometry(isometry(10.0));
+        interpolation_system.run_now(&world.res);
+        assert_eq!(
+            *world
+                .read_storage::<Transform>()
+                .get(entity)
+                .unwrap()
+                .isometry(),
+            isometry(10.0)
+        );
+
+        // and are applied to the physics world by the next physics step
+        sync_system.run_now(&world.res);
+        assert_eq!(
+            *world
+                .read_storage::<PhysicsTransform>()
+                .get(entity)
+                .unwrap()
+                .isometry(),
+            isometry(10.0)
+        );
+        assert_eq!(
+            world
+                .read_storage::<SyncedIsometry>()
+                .get(entity)
+                .unwrap()
+                .0,
+            isometry(10.0)
+        );
+
+        interpolation_system.run_now(&world.res);
+        assert_eq!(
+            *world
+                .read_storage::<Transform>()
+                .get(entity)
+                .unwrap()
+                .isometry(),
+            isometry(10.0)
+        );
+    }
+}
@@ -0,0 +1,185 @@
+use amethyst::{
+    core::Float,
+    ecs::{
+        Entities,
+        Entity,
+        Join,
+        Read,
+        ReadStorage,
+        ReaderId,
+        Resources,
+        System,
+        SystemData,
+        Write,
+        WriteStorage,
+    },
+    shrev::EventChannel,
+};
+
+use specs_physics::colliders::PhysicsCollider;
+
+use crate::{
+    events::{CollisionEvent, CollisionKind, CollisionPhase, TriggerEvent, TriggerEventKind},
+    Triggered,
+};
+
+/// The `TriggerSystem` turns proximity `CollisionEvent`s of sensor colliders
+/// into `TriggerEvent`s and keeps the `Triggered` `Component`s of sensors up to
+/// date.
+#[derive(Default)]
+pub struct TriggerSystem {
+    stay_events: bool,
+    collision_events_reader_id: Option<ReaderId<CollisionEvent>>,
+}
+
+impl<'s> System<'s> for TriggerSystem {
+    type SystemData = (
+        Entities<'s>,
+        Read<'s, EventChannel<CollisionEvent>>,
+        ReadStorage<'s, PhysicsCollider<Float>>,
+        WriteStorage<'s, Triggered>,
+        Write<'s, EventChannel<TriggerEvent>>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (entities, collision_events, physics_colliders, mut triggered, mut trigger_events) =
+            data;
+
+        let mut events = Vec::new();
+        let mut entered = Vec::new();
+
+        for collision_event in
+            collision_events.read(self.collision_events_reader_id.as_mut().unwrap())
+        {
+            if collision_event.kind != CollisionKind::Proximity {
+                continue;
+            }
+
+            // both colliders may be sensors, in which case both are triggered
+            for &(sensor, entity) in &[
+                (collision_event.entity1, collision_event.entity2),
+                (collision_event.entity2, collision_event.entity1),
+            ] {
+                let is_sensor = physics_colliders
+                    .get(sensor)
+                    .map_or(false, |physics_collider| physics_collider.sensor);
+                if !is_sensor {
+                    continue;
+                }
+
+                match collision_event.phase {
+                    CollisionPhase::Started => {
+                        if insert_triggered(&mut triggered, sensor, entity) == Some(true) {
+                            entered.push((sensor, entity));
+                            events.push(TriggerEvent {
+                                sensor,
+                                entity,
+                                kind: TriggerEventKind::Enter,
+                            });
+                        }
+                    }
+                    CollisionPhase::Stopped => {
+                        let removed = triggered
+                            .get_mut(sensor)
+                            .map_or(false, |triggered| triggered.remove(entity));
+                        if removed {
+                            events.push(TriggerEvent {
+                                sensor,
+                                entity,
+                                kind: TriggerEventKind::Exit,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        // deleted Entities never produce a stopped event, so we have to clean them
+        // up ourselves
+        let mut empty_sensors = Vec::new();
+        for (sensor, sensor_triggered) in (&entities, &mut triggered).join() {
+            for entity in sensor_triggered.retain(|entity| entities.is_alive(entity)) {
+                events.push(TriggerEvent {
+                    sensor,
+                    entity,
+                    kind: TriggerEventKind::Exit,
+                });
+            }
+
+            if sensor_triggered.is_empty() {
+                empty_sensors.push(sensor);
+                continue;
+            }
+
+            if self.stay_events {
+                for &entity in sensor_triggered.entities() {
+                    if !entered.contains(&(sensor, entity)) {
+                        events.push(TriggerEvent {
+                            sensor,
+                            entity,
+                            kind: TriggerEventKind::Stay,
+                        });
+                    }
+                }
+            }
+        }
+
+        for sensor in empty_sensors {
+            triggered.remove(sensor);
+        }
+
+        if !events.is_empty() {
+            debug!("Publishing {} TriggerEvents", events.len());
+            trigger_events.iter_write(events);
+        }
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        info!("TriggerSystem.setup");
+        Self::SystemData::setup(res);
+
+        // initialise required resources
+        res.entry::<EventChannel<TriggerEvent>>()
+            .or_insert_with(EventChannel::default);
+
+        // register reader id for the CollisionEvent channel
+        self.collision_events_reader_id = Some(
+            res.fetch_mut::<EventChannel<CollisionEvent>>()
+                .register_reader(),
+        );
+    }
+}
+
+impl TriggerSystem {
+    /// Creates a new `TriggerSystem`. If `stay_events` is enabled,
+    /// `TriggerEventKind::Stay` events are published every frame.
+    pub fn new(stay_events: bool) -> Self {
+        Self {
+            stay_events,
+            ..Default::default()
+        }
+    }
+}
+
+/// Adds the `entity` to the `Triggered` `Component` of the `sensor`, inserting
+/// the `Component` if necessary. Returns whether the `entity` was newly added
+/// or `None` if the `Component` could not be inserted.
+fn insert_triggered(
+    triggered: &mut WriteStorage<Triggered>,
+    sensor: Entity,
+    entity: Entity,
+) -> Option<bool> {
+    if let Some(sensor_triggered) = triggered.get_mut(sensor) {
+        return Some(sensor_triggered.insert(entity));
+    }
+
+    let mut sensor_triggered = Triggered::default();
+    sensor_triggered.insert(entity);
+    match triggered.insert(sensor, sensor_triggered) {
+        Ok(_) => Some(true),
+        Err(err) => {
+            warn!("Failed to insert Triggered: {}", err);
+            None
+        }
+    }
+}